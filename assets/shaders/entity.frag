@@ -0,0 +1,9 @@
+#version 330 core
+
+layout(location = 0) out vec4 out_color;
+
+in vec3 gs_color;
+
+void main(void) {
+    out_color = vec4(gs_color, 0);
+}
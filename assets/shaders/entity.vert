@@ -0,0 +1,14 @@
+#version 330 core
+
+// Entity position, drawn as point and expanded to rectangle by geometry shader
+layout(location = 0) in vec2 position;
+
+uniform vec3 point_color;
+uniform mat4 view_projection;
+
+out vec3 color;
+
+void main(void) {
+    color = point_color;
+    gl_Position = view_projection * vec4(position, 0, 1);
+}
@@ -0,0 +1,93 @@
+// Bullet/enemy broad phase: brute force against spatial hash.
+// Run with `cargo bench --no-default-features --bench collision`.
+
+use pixel_mobs_gun_rs::{game, rng, spatial};
+use std::hint::black_box;
+
+type Vec2 = game::Vec2;
+
+const ENEMY_RADIUS: f32 = game::EnemyKind::Chaser.radius();
+const MIN_INTERSECTION_DISTANCE: f32 = (ENEMY_RADIUS + game::BULLET_RADIUS) * (ENEMY_RADIUS + game::BULLET_RADIUS);
+const ITERATIONS: u32 = 50;
+
+// Entities are spread over field of given half size
+fn random_positions(rng: &mut rng::Rng, count: usize, extent: f32) -> Vec<Vec2> {
+    (0..count)
+        .map(|_| Vec2::new(rng.range_f32(-extent, extent), rng.range_f32(-extent, extent)))
+        .collect()
+}
+
+// Collision check the way engine did it before broad phase
+fn brute_force(enemies: &[Vec2], bullets: &[Vec2], killed: &mut Vec<bool>) -> usize {
+    killed.clear();
+    killed.resize(enemies.len(), false);
+
+    for (index, enemy) in enemies.iter().enumerate() {
+        for bullet in bullets {
+            if (*enemy - *bullet).length2() < MIN_INTERSECTION_DISTANCE {
+                killed[index] = true;
+                break;
+            }
+        }
+    }
+
+    killed.iter().filter(|killed| **killed).count()
+}
+
+fn spatial_hash(grid: &mut spatial::SpatialHash, enemies: &[Vec2], bullets: &[Vec2], killed: &mut Vec<bool>, buffer: &mut Vec<usize>) -> usize {
+    killed.clear();
+    killed.resize(enemies.len(), false);
+    grid.rebuild(enemies.iter().copied());
+
+    for bullet in bullets {
+        buffer.clear();
+        grid.query(*bullet, ENEMY_RADIUS + game::BULLET_RADIUS, buffer);
+
+        for &index in buffer.iter() {
+            if (enemies[index] - *bullet).length2() < MIN_INTERSECTION_DISTANCE {
+                killed[index] = true;
+            }
+        }
+    }
+
+    killed.iter().filter(|killed| **killed).count()
+}
+
+fn measure(mut function: impl FnMut() -> usize) -> (std::time::Duration, usize) {
+    let start = std::time::Instant::now();
+    let mut result = 0;
+    for _ in 0..ITERATIONS {
+        result = black_box(function());
+    }
+    (start.elapsed() / ITERATIONS, result)
+}
+
+fn main() {
+    let mut rng = rng::Rng::new(1);
+    let mut grid = spatial::SpatialHash::new(ENEMY_RADIUS + game::BULLET_RADIUS);
+    let mut killed = Vec::new();
+    let mut buffer = Vec::new();
+
+    println!("{:>8} {:>8} {:>14} {:>14} {:>8}", "enemies", "bullets", "brute force", "spatial hash", "speedup");
+
+    for (enemy_count, bullet_count) in [(10, 10), (100, 100), (250, 500), (500, 1000), (1000, 2000), (2000, 4000)] {
+        // Keep density roughly constant, as on real play field
+        let extent = (enemy_count as f32).sqrt() * 0.2;
+        let enemies = random_positions(&mut rng, enemy_count, extent);
+        let bullets = random_positions(&mut rng, bullet_count, extent);
+
+        let (brute_time, brute_hits) = measure(|| brute_force(black_box(&enemies), black_box(&bullets), &mut killed));
+        let (hash_time, hash_hits) = measure(|| spatial_hash(&mut grid, black_box(&enemies), black_box(&bullets), &mut killed, &mut buffer));
+
+        assert_eq!(brute_hits, hash_hits, "broad phase must not change collision result");
+
+        println!(
+            "{:>8} {:>8} {:>14?} {:>14?} {:>7.1}x",
+            enemy_count,
+            bullet_count,
+            brute_time,
+            hash_time,
+            brute_time.as_secs_f64() / hash_time.as_secs_f64(),
+        );
+    }
+}
@@ -0,0 +1,78 @@
+// Flow field pathfinding: field computation and whole engine ticks with enemies following it.
+// Run with `cargo bench --no-default-features --bench pathfinding`.
+
+use pixel_mobs_gun_rs::{game, level, nav, rng, spawner};
+use std::hint::black_box;
+
+type Vec2 = game::Vec2;
+
+const ITERATIONS: u32 = 50;
+
+fn measure(mut function: impl FnMut() -> usize) -> (std::time::Duration, usize) {
+    let start = std::time::Instant::now();
+    let mut result = 0;
+    for _ in 0..ITERATIONS {
+        result = black_box(function());
+    }
+    (start.elapsed() / ITERATIONS, result)
+}
+
+// Engine on default level with enemies scattered over free floor
+fn populated_engine(rng: &mut rng::Rng, enemy_count: usize) -> game::Engine {
+    let mut engine = game::Engine::new_game_with_waves(1, spawner::WaveTable::empty());
+    engine.load_level(level::Level::default_level());
+    engine.player.health = f32::INFINITY;
+
+    let bounds = engine.arena.bounds;
+    while engine.enemies.len() < enemy_count {
+        let position = Vec2::new(rng.range_f32(bounds.min.x, bounds.max.x), rng.range_f32(bounds.min.y, bounds.max.y));
+        if engine.arena.is_free(position, game::EnemyKind::Chaser.radius()) {
+            engine.enemies.push(game::Enemy::new(game::EnemyKind::Chaser, position));
+        }
+    }
+    engine
+}
+
+fn main() {
+    let mut rng = rng::Rng::new(1);
+    let level = level::Level::default_level();
+    let grid = nav::NavGrid::from_arena(&level.arena(), game::NAV_CELL_SIZE, game::NAV_CLEARANCE);
+    let mut field = nav::FlowField::new();
+
+    // Player walks around, field is recomputed for every new cell
+    let targets: Vec<Vec2> = (0..ITERATIONS)
+        .map(|index| level.player_start + Vec2::new(index as f32 * game::NAV_CELL_SIZE * 0.5, 0.0))
+        .collect();
+    let mut target_index = 0;
+    let (field_time, _) = measure(|| {
+        field = nav::FlowField::new();
+        field.update(&grid, black_box(targets[target_index % targets.len()]));
+        target_index += 1;
+        field.target().unwrap_or(0)
+    });
+    println!("flow field over {}x{} cells: {:?}", grid.width, grid.height, field_time);
+    println!();
+
+    println!("{:>8} {:>14} {:>14}", "enemies", "tick", "per enemy");
+
+    // Circling player changes cell every few ticks, so field recomputation is included
+    let inputs: Vec<game::PlayerInput> = (0..ITERATIONS)
+        .map(|index| {
+            let angle = index as f32 * 0.2;
+            game::PlayerInput { move_axis: Vec2::new(angle.cos(), angle.sin()), ..game::PlayerInput::new() }
+        })
+        .collect();
+
+    for enemy_count in [10, 100, 300, 1000] {
+        let mut engine = populated_engine(&mut rng, enemy_count);
+        let mut tick = 0;
+
+        let (tick_time, _) = measure(|| {
+            engine.update(black_box(&inputs[tick % inputs.len()]));
+            tick += 1;
+            engine.enemies.len()
+        });
+
+        println!("{:>8} {:>14?} {:>14?}", enemy_count, tick_time, tick_time / enemy_count as u32);
+    }
+}
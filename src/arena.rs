@@ -0,0 +1,155 @@
+/* Arena: rectangular play field with optional rectangular obstacles */
+
+use crate::collision::Aabb;
+use crate::game::Vec2;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Arena {
+    // Everything stays inside
+    pub bounds: Aabb,
+    // Solid rectangles inside bounds
+    pub obstacles: Vec<Aabb>,
+} /* Arena */
+
+// Point where moving circle touches wall first
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct WallHit {
+    // Fraction of displacement [0, 1] moved before contact
+    pub time: f32,
+    // Unit wall normal, pointing away from wall
+    pub normal: Vec2,
+} /* WallHit */
+
+impl WallHit {
+    // Velocity after bouncing off the wall
+    pub fn reflect(&self, velocity: Vec2) -> Vec2 {
+        velocity - self.normal * (2.0 * velocity.dot(self.normal))
+    } /* reflect */
+} /* impl WallHit */
+
+// Ray from origin along displacement enters box, returns entry time and face normal
+fn ray_enter(origin: Vec2, displacement: Vec2, aabb: &Aabb) -> Option<WallHit> {
+    let mut enter = f32::NEG_INFINITY;
+    let mut exit = f32::INFINITY;
+    let mut normal = Vec2::new(0.0, 0.0);
+
+    for (axis_origin, axis_displacement, min, max, axis_normal) in [
+        (origin.x, displacement.x, aabb.min.x, aabb.max.x, Vec2::new(1.0, 0.0)),
+        (origin.y, displacement.y, aabb.min.y, aabb.max.y, Vec2::new(0.0, 1.0)),
+    ] {
+        if axis_displacement == 0.0 {
+            if axis_origin <= min || axis_origin >= max {
+                return None;
+            }
+            continue;
+        }
+
+        let (near, far, near_normal) = if axis_displacement > 0.0 {
+            (min, max, axis_normal * -1.0)
+        } else {
+            (max, min, axis_normal)
+        };
+        let near_time = (near - axis_origin) / axis_displacement;
+        let far_time = (far - axis_origin) / axis_displacement;
+
+        if near_time > enter {
+            enter = near_time;
+            normal = near_normal;
+        }
+        exit = exit.min(far_time);
+    }
+
+    // Starting inside box doesn't count, circle is pushed out of it by resolve_circle
+    if enter > exit || !(0.0..=1.0).contains(&enter) {
+        return None;
+    }
+    Some(WallHit { time: enter, normal })
+} /* ray_enter */
+
+impl Arena {
+    pub fn new(bounds: Aabb) -> Arena {
+        Arena { bounds, obstacles: Vec::new() }
+    } /* new */
+
+    pub fn with_obstacles(bounds: Aabb, obstacles: Vec<Aabb>) -> Arena {
+        Arena { bounds, obstacles }
+    } /* with_obstacles */
+
+    // Circle center can't get closer to walls than radius
+    pub fn is_free(&self, position: Vec2, radius: f32) -> bool {
+        self.bounds.expanded(-radius).contains(position)
+            && self.obstacles.iter().all(|obstacle| (obstacle.closest_point(position) - position).length2() >= radius * radius)
+    } /* is_free */
+
+    // Move circle out of walls and obstacles by shortest way, so moving bodies slide along them
+    pub fn resolve_circle(&self, position: Vec2, radius: f32) -> Vec2 {
+        let mut position = position;
+
+        for obstacle in &self.obstacles {
+            let offset = position - obstacle.closest_point(position);
+            let distance = offset.length();
+
+            if distance >= radius {
+                continue;
+            }
+            if distance > 0.0 {
+                position += offset * ((radius - distance) / distance);
+                continue;
+            }
+
+            // Center is inside obstacle, leave it through nearest face
+            let to_min = position - obstacle.min;
+            let to_max = obstacle.max - position;
+            let exits = [
+                (to_min.x, Vec2::new(obstacle.min.x - radius, position.y)),
+                (to_max.x, Vec2::new(obstacle.max.x + radius, position.y)),
+                (to_min.y, Vec2::new(position.x, obstacle.min.y - radius)),
+                (to_max.y, Vec2::new(position.x, obstacle.max.y + radius)),
+            ];
+            position = exits.iter().min_by(|lhs, rhs| lhs.0.total_cmp(&rhs.0)).map(|exit| exit.1).unwrap_or(position);
+        }
+
+        // Bounds win over obstacles, nothing may leave the arena
+        let inner = self.bounds.expanded(-radius);
+        inner.closest_point(position)
+    } /* resolve_circle */
+
+    // First wall circle moving from origin by displacement touches
+    pub fn sweep_circle(&self, origin: Vec2, displacement: Vec2, radius: f32) -> Option<WallHit> {
+        let inner = self.bounds.expanded(-radius);
+        let mut first: Option<WallHit> = None;
+
+        if !inner.contains(origin) {
+            let normal = (inner.closest_point(origin) - origin).normalized();
+            return Some(WallHit { time: 0.0, normal });
+        }
+
+        // Leaving bounds
+        for (axis_origin, axis_displacement, min, max, axis_normal) in [
+            (origin.x, displacement.x, inner.min.x, inner.max.x, Vec2::new(1.0, 0.0)),
+            (origin.y, displacement.y, inner.min.y, inner.max.y, Vec2::new(0.0, 1.0)),
+        ] {
+            let hit = if axis_displacement > 0.0 {
+                WallHit { time: (max - axis_origin) / axis_displacement, normal: axis_normal * -1.0 }
+            } else if axis_displacement < 0.0 {
+                WallHit { time: (min - axis_origin) / axis_displacement, normal: axis_normal }
+            } else {
+                continue;
+            };
+            if hit.time <= 1.0 && first.is_none_or(|first| hit.time < first.time) {
+                first = Some(hit);
+            }
+        }
+
+        // Entering obstacles, grown by radius
+        for obstacle in &self.obstacles {
+            if let Some(hit) = ray_enter(origin, displacement, &obstacle.expanded(radius)) {
+                if first.is_none_or(|first| hit.time < first.time) {
+                    first = Some(hit);
+                }
+            }
+        }
+
+        first
+    } /* sweep_circle */
+} /* impl Arena */
@@ -0,0 +1,117 @@
+/* Camera: follows target with dead zone and smoothing, stays inside arena, shakes by trauma */
+
+use crate::collision::Aabb;
+use crate::game::Vec2;
+use crate::rng::Rng;
+use crate::view::View;
+
+// Trauma added by game events
+pub const PLAYER_HIT_TRAUMA: f32 = 0.5;
+pub const ENEMY_KILLED_TRAUMA: f32 = 0.15;
+
+pub const DEFAULT_ZOOM: f32 = 1.5;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct CameraConfig {
+    // Half size of area around camera target may move in without camera following
+    pub dead_zone: Vec2,
+    // How fast camera catches up, fraction of distance closed is 1 - e^(-follow_speed * dt)
+    pub follow_speed: f32,
+    // Shake offset at full trauma
+    pub max_shake_offset: f32,
+    // Trauma lost per second
+    pub trauma_decay: f32,
+} /* CameraConfig */
+
+#[derive(Clone, Debug)]
+pub struct Camera {
+    // World position camera looks at, without shake
+    pub position: Vec2,
+    pub zoom: f32,
+    // Shake strength in [0, 1], shake grows with its square so small hits barely move view
+    pub trauma: f32,
+    pub config: CameraConfig,
+    shake_offset: Vec2,
+    // Shake is cosmetic, so it has own generator and game stays deterministic
+    rng: Rng,
+} /* Camera */
+
+impl Default for CameraConfig {
+    fn default() -> CameraConfig {
+        CameraConfig {
+            dead_zone: Vec2::new(0.15, 0.1),
+            follow_speed: 6.0,
+            max_shake_offset: 0.04,
+            trauma_decay: 1.5,
+        }
+    }
+} /* impl Default for CameraConfig */
+
+impl Camera {
+    pub fn new(position: Vec2) -> Camera {
+        Camera {
+            position,
+            zoom: DEFAULT_ZOOM,
+            trauma: 0.0,
+            config: CameraConfig::default(),
+            shake_offset: Vec2::new(0.0, 0.0),
+            rng: Rng::new(0),
+        }
+    } /* new */
+
+    pub fn add_trauma(&mut self, amount: f32) {
+        self.trauma = (self.trauma + amount).clamp(0.0, 1.0);
+    } /* add_trauma */
+
+    // Jump to target without smoothing and stop shaking, e.g. on restart
+    pub fn snap(&mut self, target: Vec2, bounds: &Aabb, screen_size: Vec2) {
+        self.position = self.clamped(target, bounds, screen_size);
+        self.trauma = 0.0;
+        self.shake_offset = Vec2::new(0.0, 0.0);
+    } /* snap */
+
+    pub fn update(&mut self, target: Vec2, bounds: &Aabb, screen_size: Vec2, delta_time: f32) {
+        // Follow only part of target offset that is outside dead zone
+        let offset = target - self.position;
+        let dead_zone = self.config.dead_zone;
+        let outside = Vec2::new(
+            offset.x - offset.x.clamp(-dead_zone.x, dead_zone.x),
+            offset.y - offset.y.clamp(-dead_zone.y, dead_zone.y),
+        );
+        let desired = self.position + outside;
+
+        let blend = 1.0 - (-self.config.follow_speed * delta_time).exp();
+        self.position = self.clamped(self.position + (desired - self.position) * blend, bounds, screen_size);
+
+        self.trauma = (self.trauma - self.config.trauma_decay * delta_time).max(0.0);
+        let shake = self.trauma * self.trauma * self.config.max_shake_offset;
+        self.shake_offset = Vec2::new(self.rng.range_f32(-1.0, 1.0), self.rng.range_f32(-1.0, 1.0)) * shake;
+    } /* update */
+
+    // Current view of window of given size, with shake
+    pub fn view(&self, screen_size: Vec2) -> View {
+        View { center: self.position + self.shake_offset, screen_size, zoom: self.zoom }
+    } /* view */
+
+    // Current view without shake, input is converted through it so shake doesn't move aim
+    pub fn steady_view(&self, screen_size: Vec2) -> View {
+        View { center: self.position, screen_size, zoom: self.zoom }
+    } /* steady_view */
+
+    // Position closest to given one that shows nothing outside bounds; bounds smaller than view are centered
+    fn clamped(&self, position: Vec2, bounds: &Aabb, screen_size: Vec2) -> Vec2 {
+        let half_size = View { center: position, screen_size, zoom: self.zoom }.half_size();
+        let clamp_axis = |value: f32, min: f32, max: f32, half_size: f32| {
+            if max - min <= half_size * 2.0 {
+                (min + max) * 0.5
+            } else {
+                value.clamp(min + half_size, max - half_size)
+            }
+        };
+
+        Vec2::new(
+            clamp_axis(position.x, bounds.min.x, bounds.max.x, half_size.x),
+            clamp_axis(position.y, bounds.min.y, bounds.max.y, half_size.y),
+        )
+    } /* clamped */
+} /* impl Camera */
@@ -0,0 +1,102 @@
+/* Collision primitives: circles, axis-aligned boxes and swept circle tests */
+
+use crate::game::Vec2;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Circle {
+    pub center: Vec2,
+    pub radius: f32,
+} /* Circle */
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Aabb {
+    pub min: Vec2,
+    pub max: Vec2,
+} /* Aabb */
+
+impl Circle {
+    pub fn new(center: Vec2, radius: f32) -> Circle {
+        Circle { center, radius }
+    } /* new */
+
+    pub fn contains(&self, point: Vec2) -> bool {
+        (point - self.center).length2() <= self.radius * self.radius
+    } /* contains */
+
+    // Touching circles don't intersect
+    pub fn intersects(&self, other: &Circle) -> bool {
+        let radius_sum = self.radius + other.radius;
+        (other.center - self.center).length2() < radius_sum * radius_sum
+    } /* intersects */
+
+    pub fn intersects_aabb(&self, aabb: &Aabb) -> bool {
+        (aabb.closest_point(self.center) - self.center).length2() < self.radius * self.radius
+    } /* intersects_aabb */
+
+    // Circle moves by displacement during step, other stays still.
+    // Returns fraction of displacement [0, 1] at which circles start to intersect.
+    pub fn sweep(&self, displacement: Vec2, other: &Circle) -> Option<f32> {
+        let radius_sum = self.radius + other.radius;
+        let offset = self.center - other.center;
+
+        // Already intersecting at start
+        let c = offset.length2() - radius_sum * radius_sum;
+        if c < 0.0 {
+            return Some(0.0);
+        }
+
+        // Solve |offset + displacement * t| = radius_sum
+        let a = displacement.length2();
+        if a == 0.0 {
+            return None;
+        }
+        let b = offset.dot(displacement);
+        if b >= 0.0 {
+            // Moving away
+            return None;
+        }
+
+        let discriminant = b * b - a * c;
+        if discriminant < 0.0 {
+            return None;
+        }
+
+        let t = (-b - discriminant.sqrt()) / a;
+        if t <= 1.0 { Some(t) } else { None }
+    } /* sweep */
+} /* impl Circle */
+
+impl Aabb {
+    pub fn new(min: Vec2, max: Vec2) -> Aabb {
+        Aabb { min, max }
+    } /* new */
+
+    pub fn from_center(center: Vec2, half_size: Vec2) -> Aabb {
+        Aabb { min: center - half_size, max: center + half_size }
+    } /* from_center */
+
+    pub fn center(&self) -> Vec2 {
+        (self.min + self.max) * 0.5
+    } /* center */
+
+    pub fn half_size(&self) -> Vec2 {
+        (self.max - self.min) * 0.5
+    } /* half_size */
+
+    pub fn contains(&self, point: Vec2) -> bool {
+        point.x >= self.min.x && point.x <= self.max.x && point.y >= self.min.y && point.y <= self.max.y
+    } /* contains */
+
+    pub fn intersects(&self, other: &Aabb) -> bool {
+        self.min.x < other.max.x && other.min.x < self.max.x && self.min.y < other.max.y && other.min.y < self.max.y
+    } /* intersects */
+
+    pub fn closest_point(&self, point: Vec2) -> Vec2 {
+        Vec2::new(point.x.clamp(self.min.x, self.max.x), point.y.clamp(self.min.y, self.max.y))
+    } /* closest_point */
+
+    // Box grown by distance in every direction
+    pub fn expanded(&self, distance: f32) -> Aabb {
+        Aabb { min: self.min - distance, max: self.max + distance }
+    } /* expanded */
+} /* impl Aabb */
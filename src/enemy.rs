@@ -0,0 +1,237 @@
+/* Enemy archetypes and their movement behaviours */
+
+use crate::collision::Circle;
+use crate::game::{lerp, Vec2};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum EnemyKind {
+    // Homes straight to player
+    Chaser,
+    // Slow and tough chaser
+    Brute,
+    // Circles around player at fixed distance
+    Orbiter,
+    // Approaches, winds up and charges in straight line
+    Dasher,
+    // Chaser, that breaks into splitlings on death
+    Splitter,
+    Splitling,
+    // Keeps distance and fires bullets
+    Shooter,
+} /* EnemyKind */
+
+// Per-kind constants
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct EnemyStats {
+    // Full width, as for all entities
+    pub size: f32,
+    pub speed: f32,
+    pub color: [f32; 3],
+    pub max_health: f32,
+} /* EnemyStats */
+
+// Current step of multi-step behaviours (only dashers use all of them)
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum EnemyPhase {
+    Moving,
+    WindUp,
+    Dashing,
+    Recovering,
+} /* EnemyPhase */
+
+#[derive(Copy, Clone)]
+pub struct Enemy {
+    // Unique within engine, assigned by engine on first update enemy is in; 0 until then
+    pub id: u32,
+    pub kind: EnemyKind,
+    pub position: Vec2,
+    pub previous_position: Vec2,
+    // Velocity behaviour chose on last update
+    pub velocity: Vec2,
+    pub health: f32,
+
+    pub phase: EnemyPhase,
+    // Seconds spent in current phase
+    pub phase_time: f32,
+    // Locked dash direction
+    pub direction: Vec2,
+    // 1 for counter-clockwise orbiting, -1 for clockwise
+    pub orbit_direction: f32,
+    // Seconds until shooter can fire again
+    pub cooldown: f32,
+} /* Enemy */
+
+pub const ALL_ENEMY_KINDS: [EnemyKind; 7] = [
+    EnemyKind::Chaser,
+    EnemyKind::Brute,
+    EnemyKind::Orbiter,
+    EnemyKind::Dasher,
+    EnemyKind::Splitter,
+    EnemyKind::Splitling,
+    EnemyKind::Shooter,
+];
+
+// Largest enemy size, broad phase is sized by it
+pub const MAX_ENEMY_SIZE: f32 = 0.16;
+pub const MAX_ENEMY_RADIUS: f32 = MAX_ENEMY_SIZE / 2.0;
+
+pub const ORBIT_DISTANCE: f32 = 0.5;
+pub const DASH_TRIGGER_DISTANCE: f32 = 0.8;
+pub const DASH_WIND_UP: f32 = 0.6;
+pub const DASH_DURATION: f32 = 0.35;
+pub const DASH_RECOVERY: f32 = 0.8;
+pub const DASH_SPEED: f32 = 2.5;
+pub const SPLIT_COUNT: u32 = 3;
+pub const SHOOTER_DISTANCE: f32 = 0.7;
+pub const SHOOTER_FIRE_INTERVAL: f32 = 1.5;
+
+impl EnemyKind {
+    pub fn from_name(name: &str) -> Option<EnemyKind> {
+        match name {
+            "chaser" => Some(EnemyKind::Chaser),
+            "brute" => Some(EnemyKind::Brute),
+            "orbiter" => Some(EnemyKind::Orbiter),
+            "dasher" => Some(EnemyKind::Dasher),
+            "splitter" => Some(EnemyKind::Splitter),
+            "splitling" => Some(EnemyKind::Splitling),
+            "shooter" => Some(EnemyKind::Shooter),
+            _ => None,
+        }
+    } /* from_name */
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            EnemyKind::Chaser => "chaser",
+            EnemyKind::Brute => "brute",
+            EnemyKind::Orbiter => "orbiter",
+            EnemyKind::Dasher => "dasher",
+            EnemyKind::Splitter => "splitter",
+            EnemyKind::Splitling => "splitling",
+            EnemyKind::Shooter => "shooter",
+        }
+    } /* name */
+
+    pub const fn stats(&self) -> EnemyStats {
+        match self {
+            EnemyKind::Chaser    => EnemyStats { size: 0.10, speed: 1.0, color: [0.55, 0.00, 1.00], max_health: 10.0 },
+            EnemyKind::Brute     => EnemyStats { size: 0.16, speed: 0.5, color: [0.50, 0.05, 0.15], max_health: 50.0 },
+            EnemyKind::Orbiter   => EnemyStats { size: 0.08, speed: 0.9, color: [0.00, 0.85, 0.85], max_health: 10.0 },
+            EnemyKind::Dasher    => EnemyStats { size: 0.09, speed: 0.5, color: [1.00, 0.55, 0.00], max_health: 20.0 },
+            EnemyKind::Splitter  => EnemyStats { size: 0.14, speed: 0.6, color: [0.95, 0.85, 0.10], max_health: 30.0 },
+            EnemyKind::Splitling => EnemyStats { size: 0.05, speed: 1.3, color: [1.00, 1.00, 0.55], max_health: 5.0 },
+            EnemyKind::Shooter   => EnemyStats { size: 0.10, speed: 0.6, color: [1.00, 0.30, 0.60], max_health: 20.0 },
+        }
+    } /* stats */
+
+    pub const fn max_health(&self) -> f32 {
+        self.stats().max_health
+    } /* max_health */
+
+    pub const fn radius(&self) -> f32 {
+        self.stats().size / 2.0
+    } /* radius */
+} /* impl EnemyKind */
+
+impl Enemy {
+    pub fn new(kind: EnemyKind, position: Vec2) -> Enemy {
+        Enemy {
+            id: 0,
+            kind,
+            position,
+            previous_position: position,
+            velocity: Vec2::new(0.0, 0.0),
+            health: kind.max_health(),
+            phase: EnemyPhase::Moving,
+            phase_time: 0.0,
+            direction: Vec2::new(0.0, 0.0),
+            orbit_direction: 1.0,
+            cooldown: SHOOTER_FIRE_INTERVAL,
+        }
+    } /* new */
+
+    pub fn is_alive(&self) -> bool {
+        self.health > 0.0
+    } /* is_alive */
+
+    pub fn interpolated_position(&self, alpha: f32) -> Vec2 {
+        lerp(self.previous_position, self.position, alpha)
+    } /* interpolated_position */
+
+    pub fn circle(&self) -> Circle {
+        Circle::new(self.position, self.kind.radius())
+    } /* circle */
+
+    fn set_phase(&mut self, phase: EnemyPhase) {
+        self.phase = phase;
+        self.phase_time = 0.0;
+    } /* set_phase */
+
+    // Choose velocity for this tick. Returns direction to fire bullet to, if enemy shoots.
+    pub fn think(&mut self, target: Vec2, delta_time: f32) -> Option<Vec2> {
+        let stats = self.kind.stats();
+        let to_target = target - self.position;
+        let distance = to_target.length() + 0.0001;
+        let direction = to_target / distance;
+
+        self.phase_time += delta_time;
+
+        match self.kind {
+            EnemyKind::Chaser | EnemyKind::Brute | EnemyKind::Splitter | EnemyKind::Splitling => {
+                // Slow down near target, so enemies don't jitter on top of it
+                self.velocity = direction * distance.clamp(0.01, 1.00) * stats.speed;
+                None
+            }
+            EnemyKind::Orbiter => {
+                let tangent = Vec2::new(-direction.y, direction.x) * self.orbit_direction;
+                let radial = direction * (distance - ORBIT_DISTANCE).clamp(-1.0, 1.0) * 2.0;
+                let desired = radial + tangent;
+
+                self.velocity = desired.normalized() * stats.speed;
+                None
+            }
+            EnemyKind::Dasher => {
+                match self.phase {
+                    EnemyPhase::Moving => {
+                        self.velocity = direction * stats.speed;
+                        if distance < DASH_TRIGGER_DISTANCE {
+                            self.set_phase(EnemyPhase::WindUp);
+                        }
+                    }
+                    EnemyPhase::WindUp => {
+                        // Aim is tracked during wind up and locked when dash starts
+                        self.velocity = Vec2::new(0.0, 0.0);
+                        self.direction = direction;
+                        if self.phase_time >= DASH_WIND_UP {
+                            self.set_phase(EnemyPhase::Dashing);
+                        }
+                    }
+                    EnemyPhase::Dashing => {
+                        self.velocity = self.direction * DASH_SPEED;
+                        if self.phase_time >= DASH_DURATION {
+                            self.set_phase(EnemyPhase::Recovering);
+                        }
+                    }
+                    EnemyPhase::Recovering => {
+                        self.velocity = Vec2::new(0.0, 0.0);
+                        if self.phase_time >= DASH_RECOVERY {
+                            self.set_phase(EnemyPhase::Moving);
+                        }
+                    }
+                }
+                None
+            }
+            EnemyKind::Shooter => {
+                // Approach or back off to keep firing distance
+                let offset = (distance - SHOOTER_DISTANCE).clamp(-1.0, 1.0) * 4.0;
+                self.velocity = direction * offset.clamp(-1.0, 1.0) * stats.speed;
+
+                self.cooldown -= delta_time;
+                if self.cooldown <= 0.0 && distance < SHOOTER_DISTANCE * 1.5 {
+                    self.cooldown = SHOOTER_FIRE_INTERVAL;
+                    return Some(direction);
+                }
+                None
+            }
+        }
+    } /* think */
+} /* impl Enemy */
@@ -1,84 +1,760 @@
 /* Game logic implementation file */
 
+use crate::arena::Arena;
+use crate::collision::{Aabb, Circle};
+use crate::enemy::{MAX_ENEMY_RADIUS, SPLIT_COUNT};
+use crate::level::{Level, Tile};
 use crate::linmath;
+use crate::nav::{FlowField, NavGrid};
+use crate::pickup::{self, Pickup, PickupKind};
+use crate::pool::Pool;
+use crate::rng::Rng;
+use crate::score::{RunSummary, Score};
+use crate::spatial::SpatialHash;
+use crate::spawner::{Spawner, WaveTable};
+use crate::steering::SteeringConfig;
+use crate::weapon::{Weapon, ALL_WEAPON_KINDS, WEAPON_COUNT};
 pub type Vec2 = linmath::Vec2<f32>;
 
+pub use crate::enemy::{Enemy, EnemyKind, EnemyPhase, EnemyStats};
+pub use crate::weapon::{WeaponKind, WeaponStats};
+
+// Player controls for single tick, independent of input device
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PlayerInput {
+    pub move_axis: Vec2,
+    pub aim: Vec2,
+    // Trigger is held
+    pub fire: bool,
+    pub reload: bool,
+    // Weapon slot to switch to
+    pub switch_weapon: Option<usize>,
+} /* PlayerInput */
+
 #[derive(Copy, Clone)]
 pub struct Player {
     pub position: Vec2,
+    pub previous_position: Vec2,
     pub health: f32,
+    // Seconds left until player can be hit again
+    pub invulnerable_time: f32,
+    // Velocity, player is pushed with after hit, decays over time
+    pub knockback: Vec2,
+    // One weapon of every kind, in ALL_WEAPON_KINDS order
+    pub weapons: [Weapon; WEAPON_COUNT],
+    pub current_weapon: usize,
+    // Seconds left of timed pickup effects
+    pub weapon_boost_time: f32,
+    pub speed_boost_time: f32,
 } /* Player */
 
-#[derive(Copy, Clone)]
-pub struct Enemy {
-    pub position: Vec2,
-    pub health: f32,
-} /* Enemy */
-
 #[derive(Copy, Clone)]
 pub struct Bullet {
     pub position: Vec2,
+    pub previous_position: Vec2,
     pub velocity: Vec2,
+    // Seconds left before bullet despawns
+    pub lifetime: f32,
+    pub damage: f32,
+    // Enemies bullet can still pass through
+    pub pierce: u32,
+    // Enemies bullet has hit
+    pub hits: u32,
+    // Ids of enemies bullet has hit, first `hits` of them are set
+    pub hit_enemies: [u32; MAX_BULLET_HITS],
+    // Times bullet can still ricochet off walls, it stops on wall otherwise
+    pub bounces: u32,
 } /* Bullet */
 
+// Something noticeable happened during tick, front end may react to it
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum GameEvent {
+    EnemyHit { kind: EnemyKind, position: Vec2, damage: f32 },
+    EnemyKilled { kind: EnemyKind, position: Vec2 },
+    PlayerHit { position: Vec2, damage: f32 },
+    PlayerDied { position: Vec2 },
+    WaveStarted { wave: u32 },
+    PickupCollected { kind: PickupKind, position: Vec2 },
+    GameOver,
+} /* GameEvent */
+
+// Playing -> PlayerDead -> GameOver; Playing <-> Paused
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum GameState {
+    Playing,
+    // Player is dead, world still runs for DEATH_DURATION seconds
+    PlayerDead,
+    GameOver,
+    Paused,
+} /* GameState */
+
 pub struct Engine {
     pub player: Player,
     pub enemies: Vec<Enemy>,
-    pub bullets: Vec<Bullet>,
+    pub bullets: Pool<Bullet>,
+    // Bullets fired by enemies, they only hit player
+    pub enemy_bullets: Pool<Bullet>,
+    pub pickups: Vec<Pickup>,
+    // Play field and obstacles, bodies slide along walls, bullets stop or bounce
+    pub arena: Arena,
+    // Tile map arena was built from, if any
+    pub level: Option<Level>,
+    // Blocked cells of arena, enemies path around obstacles along flow field when it is set
+    pub nav: Option<NavGrid>,
+    // Events emitted during last update
+    pub events: Vec<GameEvent>,
+    pub spawner: Spawner,
+    pub steering: SteeringConfig,
+    pub score: Score,
+
+    state: GameState,
+    // Seconds spent in current state
+    state_time: f32,
+
+    seed: u64,
+    rng: Rng,
+    tick: u64,
+    // Id next new enemy gets
+    next_enemy_id: u32,
+
+    // Broad phase and scratch buffers, reused between ticks
+    enemy_grid: SpatialHash,
+    query_buffer: Vec<usize>,
+    steering_buffer: Vec<Vec2>,
+    hit_buffer: Vec<(f32, usize)>,
+    // Paths to player cell, recomputed when player changes cell
+    flow_field: FlowField,
 } /* Engine */
 
+// Sizes are full widths, as entities are rendered; collision uses half of them as radii.
+// Enemy sizes are per kind (see EnemyKind::stats).
 pub const PLAYER_SIZE: f32 = 0.05;
-pub const ENEMY_SIZE: f32 = 0.1;
-pub const BULLET_SIZE: f32 = 0.01;
+pub const BULLET_SIZE: f32 = 0.02;
+
+pub const PLAYER_RADIUS: f32 = PLAYER_SIZE / 2.0;
+pub const BULLET_RADIUS: f32 = BULLET_SIZE / 2.0;
+
+pub const PLAYER_SPEED: f32 = 0.8;
+pub const BULLET_SPEED: f32 = 1.0;
+pub const BULLET_LIFETIME: f32 = 3.0;
+pub const BULLET_DAMAGE: f32 = 10.0;
+pub const MAX_BULLETS: usize = 4096;
+// More enemies than any bullet can pierce
+pub const MAX_BULLET_HITS: usize = 16;
+pub const ENEMY_BULLET_SPEED: f32 = 0.6;
+pub const ENEMY_BULLET_DAMAGE: f32 = 10.0;
+pub const MAX_ENEMY_BULLETS: usize = 1024;
+
+pub const ARENA_HALF_WIDTH: f32 = 1.5;
+pub const ARENA_HALF_HEIGHT: f32 = 1.0;
+
+// Navigation grid cell size and distance its free cells keep from walls
+pub const NAV_CELL_SIZE: f32 = 0.05;
+pub const NAV_CLEARANCE: f32 = 0.03;
+pub const PLAYER_MAX_HEALTH: f32 = 100.0;
+// Player health lost on enemy contact, player is invulnerable for a while after that
+pub const ENEMY_CONTACT_DAMAGE: f32 = 20.0;
+pub const INVULNERABILITY_DURATION: f32 = 1.0;
+pub const KNOCKBACK_SPEED: f32 = 2.0;
+// Fraction of knockback velocity lost per second
+pub const KNOCKBACK_DAMPING: f32 = 8.0;
+pub const DEATH_DURATION: f32 = 2.0;
+
+// Simulation runs at fixed rate, independent of frame rate
+pub const TICK_RATE: f64 = 120.0;
+pub const TICK_DURATION: f32 = (1.0 / TICK_RATE) as f32;
+pub const MAX_TICKS_PER_FRAME: u32 = 12;
+
+impl PlayerInput {
+    pub fn new() -> PlayerInput {
+        PlayerInput {
+            move_axis: Vec2::new(0.0, 0.0),
+            aim: Vec2::new(0.0, 0.0),
+            fire: false,
+            reload: false,
+            switch_weapon: None,
+        }
+    } /* new */
+} /* impl PlayerInput */
+
+impl Default for PlayerInput {
+    fn default() -> PlayerInput {
+        PlayerInput::new()
+    }
+}
+
+pub(crate) fn lerp(from: Vec2, to: Vec2, alpha: f32) -> Vec2 {
+    from + (to - from) * alpha
+} /* lerp */
+
+impl Player {
+    pub fn new(position: Vec2) -> Player {
+        Player {
+            position,
+            previous_position: position,
+            health: PLAYER_MAX_HEALTH,
+            invulnerable_time: 0.0,
+            knockback: Vec2::new(0.0, 0.0),
+            weapons: ALL_WEAPON_KINDS.map(Weapon::new),
+            current_weapon: 0,
+            weapon_boost_time: 0.0,
+            speed_boost_time: 0.0,
+        }
+    } /* new */
+
+    pub fn is_alive(&self) -> bool {
+        self.health > 0.0
+    } /* is_alive */
+
+    pub fn is_invulnerable(&self) -> bool {
+        self.invulnerable_time > 0.0
+    } /* is_invulnerable */
+
+    pub fn interpolated_position(&self, alpha: f32) -> Vec2 {
+        lerp(self.previous_position, self.position, alpha)
+    } /* interpolated_position */
+
+    pub fn circle(&self) -> Circle {
+        Circle::new(self.position, PLAYER_RADIUS)
+    } /* circle */
+
+    pub fn weapon(&self) -> &Weapon {
+        &self.weapons[self.current_weapon]
+    } /* weapon */
+
+    pub fn weapon_mut(&mut self) -> &mut Weapon {
+        &mut self.weapons[self.current_weapon]
+    } /* weapon_mut */
+
+    pub fn speed(&self) -> f32 {
+        if self.speed_boost_time > 0.0 { PLAYER_SPEED * pickup::SPEED_BOOST_SCALE } else { PLAYER_SPEED }
+    } /* speed */
+
+    pub fn collect(&mut self, kind: PickupKind) {
+        match kind {
+            PickupKind::Health => self.health = (self.health + pickup::HEALTH_PICKUP_AMOUNT).min(PLAYER_MAX_HEALTH),
+            PickupKind::Ammo => {
+                for weapon in &mut self.weapons {
+                    weapon.ammo = weapon.stats().magazine;
+                    weapon.reload_time = 0.0;
+                }
+            }
+            PickupKind::WeaponBoost => self.weapon_boost_time = pickup::WEAPON_BOOST_DURATION,
+            PickupKind::SpeedBoost => self.speed_boost_time = pickup::SPEED_BOOST_DURATION,
+        }
+    } /* collect */
+
+    // Returns false if there is no such slot
+    pub fn switch_weapon(&mut self, slot: usize) -> bool {
+        if slot >= self.weapons.len() {
+            return false;
+        }
+        if slot != self.current_weapon {
+            self.weapon_mut().holster();
+            self.current_weapon = slot;
+        }
+        true
+    } /* switch_weapon */
+} /* impl Player */
+
+impl Bullet {
+    pub fn new(position: Vec2, velocity: Vec2) -> Bullet {
+        Bullet {
+            position,
+            previous_position: position,
+            velocity,
+            lifetime: BULLET_LIFETIME,
+            damage: BULLET_DAMAGE,
+            pierce: 0,
+            hits: 0,
+            hit_enemies: [0; MAX_BULLET_HITS],
+            bounces: 0,
+        }
+    } /* new */
+
+    pub fn interpolated_position(&self, alpha: f32) -> Vec2 {
+        lerp(self.previous_position, self.position, alpha)
+    } /* interpolated_position */
+
+    pub fn circle(&self) -> Circle {
+        Circle::new(self.position, BULLET_RADIUS)
+    } /* circle */
+} /* impl Bullet */
 
 impl Engine {
     pub fn new() -> Engine {
+        Engine::with_seed(0)
+    } /* new */
+
+    // Empty engine, all randomness derived from seed
+    pub fn with_seed(seed: u64) -> Engine {
         Engine {
-            player: Player { position: Vec2::new(0.0, 0.0), health: 100.0 },
+            player: Player::new(Vec2::new(0.0, 0.0)),
             enemies: Vec::<Enemy>::new(),
-            bullets: Vec::<Bullet>::new(),
+            bullets: Pool::new(MAX_BULLETS),
+            enemy_bullets: Pool::new(MAX_ENEMY_BULLETS),
+            pickups: Vec::new(),
+            arena: Arena::new(Aabb::from_center(Vec2::new(0.0, 0.0), Vec2::new(ARENA_HALF_WIDTH, ARENA_HALF_HEIGHT))),
+            level: None,
+            nav: None,
+            events: Vec::new(),
+            spawner: Spawner::new(WaveTable::empty()),
+            steering: SteeringConfig::default(),
+            score: Score::new(),
+            state: GameState::Playing,
+            state_time: 0.0,
+            seed,
+            rng: Rng::new(seed),
+            tick: 0,
+            next_enemy_id: 1,
+            enemy_grid: SpatialHash::new(MAX_ENEMY_RADIUS + PLAYER_RADIUS.max(BULLET_RADIUS)),
+            query_buffer: Vec::new(),
+            steering_buffer: Vec::new(),
+            hit_buffer: Vec::new(),
+            flow_field: FlowField::new(),
         }
-    } /* new */
+    } /* with_seed */
+
+    // Engine in state new game starts from, with built-in waves
+    pub fn new_game(seed: u64) -> Engine {
+        Engine::new_game_with_waves(seed, WaveTable::default_waves())
+    } /* new_game */
+
+    pub fn new_game_with_waves(seed: u64, waves: WaveTable) -> Engine {
+        let mut engine = Engine::with_seed(seed);
+        engine.spawner = Spawner::new(waves);
+        engine
+    } /* new_game_with_waves */
+
+    // Replace arena by level walls, place player and level pickups, enemies spawn at level spawn points
+    pub fn load_level(&mut self, level: Level) {
+        self.arena = level.arena();
+        self.rebuild_nav();
+        self.player.position = level.player_start;
+        self.player.previous_position = level.player_start;
+        self.spawner.set_spawn_points(level.spawn_points.clone());
+
+        self.pickups.clear();
+        for &(kind, position) in &level.pickups {
+            let mut pickup = Pickup::new(kind, position);
+            // Placed pickups wait for player
+            pickup.lifetime = f32::INFINITY;
+            self.pickups.push(pickup);
+        }
+
+        self.level = Some(level);
+    } /* load_level */
+
+    // Navigation grid for current arena obstacles, call after changing them; open arena needs none
+    pub fn rebuild_nav(&mut self) {
+        self.nav = if self.arena.obstacles.is_empty() {
+            None
+        } else {
+            Some(NavGrid::from_arena(&self.arena, NAV_CELL_SIZE, NAV_CLEARANCE))
+        };
+        self.flow_field = FlowField::new();
+    } /* rebuild_nav */
+
+    // Tile under point, arena without level is all floor inside bounds
+    pub fn tile_at(&self, position: Vec2) -> Tile {
+        match &self.level {
+            Some(level) => level.tile_at(position),
+            None if self.arena.bounds.contains(position) => Tile::Floor,
+            None => Tile::Wall,
+        }
+    } /* tile_at */
+
+    pub fn is_wall(&self, position: Vec2) -> bool {
+        self.tile_at(position) == Tile::Wall
+    } /* is_wall */
+
+    // Can something at `from` see `to`, walls block sight
+    pub fn line_of_sight(&self, from: Vec2, to: Vec2) -> bool {
+        match &self.level {
+            Some(level) => level.line_of_sight(from, to),
+            None => self.arena.sweep_circle(from, to - from, 0.0).is_none(),
+        }
+    } /* line_of_sight */
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    } /* seed */
+
+    // Number of ticks simulated since engine creation
+    pub fn tick(&self) -> u64 {
+        self.tick
+    } /* tick */
+
+    pub fn rng(&mut self) -> &mut Rng {
+        &mut self.rng
+    } /* rng */
+
+    pub fn state(&self) -> GameState {
+        self.state
+    } /* state */
+
+    // Seconds spent in current state
+    pub fn state_time(&self) -> f32 {
+        self.state_time
+    } /* state_time */
+
+    // Statistics of run so far
+    pub fn summary(&self) -> RunSummary {
+        self.score.summary(self.spawner.wave(), self.seed)
+    } /* summary */
+
+    fn set_state(&mut self, state: GameState) {
+        self.state = state;
+        self.state_time = 0.0;
+    } /* set_state */
+
+    // Pause is only possible during play, returns true if state changed.
+    // Time spent in Playing state is kept over pause.
+    pub fn pause(&mut self) -> bool {
+        if self.state != GameState::Playing {
+            return false;
+        }
+        self.state = GameState::Paused;
+        true
+    } /* pause */
+
+    pub fn resume(&mut self) -> bool {
+        if self.state != GameState::Paused {
+            return false;
+        }
+        self.state = GameState::Playing;
+        true
+    } /* resume */
+
+    pub fn toggle_pause(&mut self) -> bool {
+        self.pause() || self.resume()
+    } /* toggle_pause */
+
+    pub fn fixed_step() -> crate::timer::FixedStep {
+        crate::timer::FixedStep::new(TICK_RATE, MAX_TICKS_PER_FRAME)
+    } /* fixed_step */
+
+    // Advance simulation by exactly one tick (TICK_DURATION seconds).
+    // Result depends only on previous state and input, so ticks can be replayed.
+    pub fn update(&mut self, input: &PlayerInput) {
+        self.events.clear();
+
+        // Paused and finished games are frozen
+        if self.state == GameState::Paused || self.state == GameState::GameOver {
+            return;
+        }
+
+        let delta_time = TICK_DURATION;
+        self.tick += 1;
+
+        self.state_time += delta_time;
+        if self.state == GameState::PlayerDead && self.state_time >= DEATH_DURATION {
+            self.set_state(GameState::GameOver);
+            self.events.push(GameEvent::GameOver);
+            return;
+        }
+
+        if self.state == GameState::Playing {
+            self.score.update(delta_time);
+        }
+
+        // Dead player doesn't act
+        let input = match self.state {
+            GameState::Playing => *input,
+            _ => PlayerInput::new(),
+        };
 
-    pub fn update(&mut self, delta_time: f32) {
-        // Update bullets and enemies positions
-        for bullet in &mut self.bullets {
+        // Save positions for render interpolation
+        self.player.previous_position = self.player.position;
+        for bullet in self.bullets.iter_mut().chain(self.enemy_bullets.iter_mut()) {
+            bullet.previous_position = bullet.position;
+        }
+        for enemy in &mut self.enemies {
+            enemy.previous_position = enemy.position;
+        }
+
+        self.spawner.update(delta_time, &self.arena.bounds, &mut self.rng, &mut self.enemies, &mut self.events);
+
+        // Apply player input
+        if let Some(slot) = input.switch_weapon {
+            self.player.switch_weapon(slot);
+        }
+        if input.reload {
+            self.player.weapon_mut().reload();
+        }
+        let aim = input.aim - self.player.position;
+        // Trigger is ignored while there is nothing to aim at
+        if self.player.weapon_mut().update(input.fire && aim.length2() > 0.0, delta_time) {
+            self.fire(aim.normalized());
+        }
+
+        // Update player, bullets and enemies positions
+        self.player.position += (input.move_axis * self.player.speed() + self.player.knockback) * delta_time;
+        self.player.position = self.arena.resolve_circle(self.player.position, PLAYER_RADIUS);
+        self.player.knockback *= 1.0 - (KNOCKBACK_DAMPING * delta_time).min(1.0);
+        self.player.invulnerable_time = (self.player.invulnerable_time - delta_time).max(0.0);
+        self.player.weapon_boost_time = (self.player.weapon_boost_time - delta_time).max(0.0);
+        self.player.speed_boost_time = (self.player.speed_boost_time - delta_time).max(0.0);
+
+        for bullet in self.bullets.iter_mut().chain(self.enemy_bullets.iter_mut()) {
             bullet.position += bullet.velocity * delta_time;
+            bullet.lifetime -= delta_time;
+        }
+        if let Some(nav) = &self.nav {
+            self.flow_field.update(nav, self.player.position);
         }
         for enemy in &mut self.enemies {
-            let position_delta = self.player.position - enemy.position;
-            let length: f32 = position_delta.length() + 0.0001;
+            if let Some(direction) = enemy.think(self.player.position, delta_time) {
+                let position = enemy.position + direction * (enemy.kind.radius() + BULLET_RADIUS);
+                let mut bullet = Bullet::new(position, direction * ENEMY_BULLET_SPEED);
 
-            enemy.position += (position_delta / length * length.clamp(0.01, 1.00)) * delta_time;
+                bullet.damage = ENEMY_BULLET_DAMAGE;
+                self.enemy_bullets.spawn(bullet);
+            }
+
+            // Homing enemies follow path around obstacles instead of straight line, keeping their speed
+            let homing = match enemy.kind {
+                EnemyKind::Chaser | EnemyKind::Brute | EnemyKind::Splitter | EnemyKind::Splitling => true,
+                EnemyKind::Dasher => enemy.phase == EnemyPhase::Moving,
+                EnemyKind::Orbiter | EnemyKind::Shooter => false,
+            };
+            let path = self.nav.as_ref().filter(|_| homing).and_then(|nav| self.flow_field.direction(nav, enemy.position));
+            if let Some(direction) = path {
+                enemy.velocity = direction * enemy.velocity.length();
+            }
         }
 
-        // Intersect player with enemies
-        for enemy in &self.enemies {
-            const MIN_INTERSECTION_DISTANCE: f32 = PLAYER_SIZE * PLAYER_SIZE + ENEMY_SIZE * ENEMY_SIZE;
-            let distance = (enemy.position - self.player.position).length2();
+        // Steer crowd apart and around obstacles, corrections are computed from same state for all enemies
+        self.enemy_grid.rebuild(self.enemies.iter().map(|enemy| enemy.position));
+        self.steering_buffer.clear();
+        let query_radius = self.steering.query_radius(MAX_ENEMY_RADIUS);
+        for index in 0..self.enemies.len() {
+            self.query_buffer.clear();
+            self.enemy_grid.query(self.enemies[index].position, query_radius, &mut self.query_buffer);
+            self.steering_buffer.push(self.steering.steer(index, &self.enemies, &self.query_buffer, &self.arena.obstacles));
+        }
+        for (enemy, correction) in self.enemies.iter_mut().zip(&self.steering_buffer) {
+            // Charging dashers keep their line
+            if enemy.phase != EnemyPhase::Dashing {
+                let max_speed = enemy.kind.stats().speed * 1.5;
+                let velocity = enemy.velocity + *correction;
+                let speed = velocity.length();
+
+                enemy.velocity = if speed > max_speed { velocity * (max_speed / speed) } else { velocity };
+            }
+            enemy.position += enemy.velocity * delta_time;
+            enemy.position = self.arena.resolve_circle(enemy.position, enemy.kind.radius());
+        }
+
+        self.enemy_grid.rebuild(self.enemies.iter().map(|enemy| enemy.position));
+
+        // Collect pickups by overlap, uncollected ones expire
+        let player_circle = self.player.circle();
+        let player_collects = self.state == GameState::Playing;
+        self.pickups.retain_mut(|pickup| {
+            pickup.lifetime -= delta_time;
+            if player_collects && pickup.circle().intersects(&player_circle) {
+                self.player.collect(pickup.kind);
+                self.events.push(GameEvent::PickupCollected { kind: pickup.kind, position: pickup.position });
+                return false;
+            }
+            pickup.lifetime > 0.0
+        });
+
+        // Intersect player with enemies, single hit per invulnerability period
+        if self.state == GameState::Playing && !self.player.is_invulnerable() {
+            let player_circle = self.player.circle();
+            self.query_buffer.clear();
+            self.enemy_grid.query(self.player.position, MAX_ENEMY_RADIUS + PLAYER_RADIUS, &mut self.query_buffer);
+
+            let hit_enemy = self.query_buffer
+                .iter()
+                .find(|index| self.enemies[**index].circle().intersects(&player_circle))
+                .map(|index| self.enemies[*index]);
 
-            if distance < MIN_INTERSECTION_DISTANCE {
-                self.player.health -= 5.0;
+            if let Some(enemy) = hit_enemy {
+                self.hit_player(enemy.position, ENEMY_CONTACT_DAMAGE);
             }
         }
 
-        // Intersect enemies with bullets
-        let new_enemy_list: Vec<Enemy> = self.enemies
-            .iter()
-            .filter(|enemy| {
-                for bullet in &self.bullets {
-                    const MIN_INTERSECTION_DISTANCE: f32 = ENEMY_SIZE * ENEMY_SIZE + BULLET_SIZE * BULLET_SIZE;
-                    let distance = (enemy.position - bullet.position).length2();
+        // Intersect enemy bullets with player, they fly through invulnerable or dead one
+        let player_circle = self.player.circle();
+        let player_hittable = self.state == GameState::Playing && !self.player.is_invulnerable();
+        let mut player_hit: Option<(Vec2, f32)> = None;
+        self.enemy_bullets.retain(|bullet| {
+            let displacement = bullet.position - bullet.previous_position;
+            let wall_time = self.arena.sweep_circle(bullet.previous_position, displacement, BULLET_RADIUS).map(|hit| hit.time);
+            let hit_time = if player_hittable {
+                Circle::new(bullet.previous_position, BULLET_RADIUS).sweep(displacement, &player_circle)
+            } else {
+                None
+            };
 
-                    if distance < MIN_INTERSECTION_DISTANCE {
-                        return false
+            // Whatever is met first stops bullet, enemy bullets don't bounce
+            match (hit_time, wall_time) {
+                (Some(hit_time), wall_time) if wall_time.is_none_or(|wall_time| hit_time <= wall_time) => {
+                    if player_hit.is_none() {
+                        player_hit = Some((bullet.previous_position, bullet.damage));
                     }
+                    false
                 }
+                (_, Some(_)) => false,
+                // Expiring bullet still hits on its last step
+                _ => bullet.lifetime > 0.0,
+            }
+        });
+        if let Some((source, damage)) = player_hit {
+            self.hit_player(source, damage);
+        }
+
+        // Enemies spawned since last tick get their ids, bullets tell enemies they hit by them
+        for enemy in &mut self.enemies {
+            if enemy.id == 0 {
+                enemy.id = self.next_enemy_id;
+                self.next_enemy_id += 1;
+            }
+        }
+
+        // Intersect enemies with bullets, bullets are swept along their path so they can't tunnel.
+        // Bullet hits first enemies on its path until its pierce runs out and despawns, expired ones after their last step.
+        // Walls cut path short, bullet bounces off them while it can and stops on them otherwise.
+        self.bullets.retain(|bullet| {
+            let displacement = bullet.position - bullet.previous_position;
+            let bullet_circle = Circle::new(bullet.previous_position, BULLET_RADIUS);
+            let wall_hit = self.arena.sweep_circle(bullet.previous_position, displacement, BULLET_RADIUS);
+            let wall_time = wall_hit.map_or(1.0, |hit| hit.time);
 
-                return true
-            })
-            .cloned()
-            .collect();
-        self.enemies = new_enemy_list;
+            self.query_buffer.clear();
+            self.enemy_grid.query(
+                bullet.previous_position + displacement * 0.5,
+                displacement.length() * 0.5 + MAX_ENEMY_RADIUS + BULLET_RADIUS,
+                &mut self.query_buffer
+            );
+
+            self.hit_buffer.clear();
+            for &index in &self.query_buffer {
+                let enemy = &self.enemies[index];
+                // Piercing bullet can still be inside enemy it hit on previous ticks
+                if !enemy.is_alive() || bullet.hit_enemies[..(bullet.hits as usize).min(MAX_BULLET_HITS)].contains(&enemy.id) {
+                    continue;
+                }
+                if let Some(time) = bullet_circle.sweep(displacement, &enemy.circle()) {
+                    // Enemies behind wall are safe
+                    if time > wall_time {
+                        continue;
+                    }
+                    self.hit_buffer.push((time, index));
+                }
+            }
+            self.hit_buffer.sort_by(|lhs, rhs| lhs.0.total_cmp(&rhs.0));
+
+            for &(_, index) in &self.hit_buffer {
+                let enemy = &mut self.enemies[index];
+
+                enemy.health -= bullet.damage;
+                self.events.push(GameEvent::EnemyHit { kind: enemy.kind, position: enemy.position, damage: bullet.damage });
+                if !enemy.is_alive() {
+                    self.events.push(GameEvent::EnemyKilled { kind: enemy.kind, position: enemy.position });
+                    self.score.record_kill(enemy.kind);
+                }
+
+                if bullet.hits == 0 {
+                    self.score.bullets_hit += 1;
+                }
+                if let Some(hit_enemy) = bullet.hit_enemies.get_mut(bullet.hits as usize) {
+                    *hit_enemy = enemy.id;
+                }
+                bullet.hits += 1;
+                if bullet.hits > bullet.pierce {
+                    return false;
+                }
+            }
+
+            let Some(wall_hit) = wall_hit else {
+                return bullet.lifetime > 0.0;
+            };
+            if bullet.bounces == 0 || bullet.lifetime <= 0.0 {
+                return false;
+            }
+
+            // Rest of tick displacement is mirrored off the wall
+            let contact = bullet.previous_position + displacement * wall_hit.time;
+            bullet.position = contact + wall_hit.reflect(displacement) * (1.0 - wall_hit.time);
+            bullet.velocity = wall_hit.reflect(bullet.velocity);
+            bullet.bounces -= 1;
+            // Reflected position could end up in another wall in a corner
+            bullet.position = self.arena.resolve_circle(bullet.position, BULLET_RADIUS);
+            true
+        });
+
+        // Killed enemies drop pickups, splitters break into splitlings
+        for index in 0..self.enemies.len() {
+            let enemy = self.enemies[index];
+            if enemy.is_alive() {
+                continue;
+            }
+            if let Some(pickup) = Pickup::roll(enemy.kind, enemy.position, &mut self.rng) {
+                self.pickups.push(pickup);
+            }
+            if enemy.kind != EnemyKind::Splitter {
+                continue;
+            }
+
+            for split in 0..SPLIT_COUNT {
+                let angle = std::f32::consts::TAU * split as f32 / SPLIT_COUNT as f32;
+                let offset = Vec2::new(angle.cos(), angle.sin()) * enemy.kind.radius() * 0.5;
+                let mut splitling = Enemy::new(EnemyKind::Splitling, enemy.position + offset);
+
+                splitling.previous_position = enemy.previous_position + offset;
+                self.enemies.push(splitling);
+            }
+        }
+
+        self.enemies.retain(|enemy| enemy.is_alive());
     } /* update */
+
+    // Spawn bullets of current weapon shot
+    fn fire(&mut self, direction: Vec2) {
+        let stats = self.player.weapon().stats();
+
+        for _ in 0..stats.pellets {
+            let angle = direction.y.atan2(direction.x) + self.rng.range_f32(-0.5, 0.5) * stats.spread;
+            let pellet_direction = Vec2::new(angle.cos(), angle.sin());
+            let mut bullet = Bullet::new(self.player.position + pellet_direction * 0.01, pellet_direction * stats.bullet_speed);
+
+            bullet.lifetime = stats.bullet_lifetime;
+            bullet.damage = stats.damage;
+            bullet.pierce = stats.pierce;
+            bullet.bounces = stats.bounces;
+            if self.player.weapon_boost_time > 0.0 {
+                bullet.damage *= pickup::WEAPON_BOOST_DAMAGE_SCALE;
+                bullet.pierce += pickup::WEAPON_BOOST_EXTRA_PIERCE;
+            }
+            if self.bullets.spawn(bullet) {
+                self.score.bullets_fired += 1;
+            }
+        }
+    } /* fire */
+
+    // Damage player, source is pushed away from
+    fn hit_player(&mut self, source: Vec2, damage: f32) {
+        let away = self.player.position - source;
+        let direction = if away.length2() > 0.0 { away.normalized() } else { Vec2::new(0.0, 1.0) };
+
+        self.player.health = (self.player.health - damage).max(0.0);
+        self.player.invulnerable_time = INVULNERABILITY_DURATION;
+        self.player.knockback = direction * KNOCKBACK_SPEED;
+        self.events.push(GameEvent::PlayerHit { position: self.player.position, damage });
+
+        if !self.player.is_alive() {
+            self.set_state(GameState::PlayerDead);
+            self.events.push(GameEvent::PlayerDied { position: self.player.position });
+        }
+    } /* hit_player */
 } /* impl Engine */
+
+impl Default for Engine {
+    fn default() -> Engine {
+        Engine::new()
+    }
+}
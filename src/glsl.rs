@@ -0,0 +1,208 @@
+/* GLSL shader stages and build errors, driver logs are mapped back to shader source; no OpenGL dependency.
+   Shader files on disk can be polled for changes, so they are reloaded during development.
+   Shaders are checked offline by tests, which parse them with full GLSL parser (see tests/glsl.rs). */
+
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ShaderStage {
+    Vertex,
+    Geometry,
+    Fragment,
+} /* ShaderStage */
+
+// Sources of all stages of one program, named by stage so they can't be passed in wrong order
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ProgramSources<'a> {
+    pub vertex: &'a str,
+    pub geometry: Option<&'a str>,
+    pub fragment: &'a str,
+} /* ProgramSources */
+
+// Sources of all stages of one program read from files
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LoadedSources {
+    pub vertex: String,
+    pub geometry: Option<String>,
+    pub fragment: String,
+} /* LoadedSources */
+
+// Shader files of one program, e.g. assets/shaders/entity.vert, entity.geom and entity.frag
+#[derive(Clone, Debug)]
+pub struct ProgramFiles {
+    pub directory: PathBuf,
+    pub name: String,
+    // Modification times of stage files when they were last polled, None for missing files
+    modified: Vec<(ShaderStage, Option<SystemTime>)>,
+} /* ProgramFiles */
+
+#[derive(Debug)]
+pub enum ShaderError {
+    Io(std::io::Error),
+    // Driver log has offending source lines added after lines it refers to
+    Compile { stage: ShaderStage, log: String },
+    Link { log: String },
+    // Program can't be built without this stage
+    MissingStage(ShaderStage),
+} /* ShaderError */
+
+impl ShaderStage {
+    pub fn name(self) -> &'static str {
+        match self {
+            ShaderStage::Vertex => "vertex",
+            ShaderStage::Geometry => "geometry",
+            ShaderStage::Fragment => "fragment",
+        }
+    } /* name */
+
+    // Extension of shader file of the stage, e.g. assets/shaders/entity.vert
+    pub fn extension(self) -> &'static str {
+        match self {
+            ShaderStage::Vertex => "vert",
+            ShaderStage::Geometry => "geom",
+            ShaderStage::Fragment => "frag",
+        }
+    } /* extension */
+
+    pub fn from_extension(extension: &str) -> Option<ShaderStage> {
+        match extension {
+            "vert" => Some(ShaderStage::Vertex),
+            "geom" => Some(ShaderStage::Geometry),
+            "frag" => Some(ShaderStage::Fragment),
+            _ => None,
+        }
+    } /* from_extension */
+} /* impl ShaderStage */
+
+impl<'a> ProgramSources<'a> {
+    // Stages present, in pipeline order
+    pub fn stages(&self) -> impl Iterator<Item = (ShaderStage, &'a str)> {
+        [
+            (ShaderStage::Vertex, Some(self.vertex)),
+            (ShaderStage::Geometry, self.geometry),
+            (ShaderStage::Fragment, Some(self.fragment)),
+        ]
+        .into_iter()
+        .filter_map(|(stage, source)| source.map(|source| (stage, source)))
+    } /* stages */
+} /* impl ProgramSources */
+
+impl LoadedSources {
+    pub fn sources(&self) -> ProgramSources<'_> {
+        ProgramSources { vertex: &self.vertex, geometry: self.geometry.as_deref(), fragment: &self.fragment }
+    } /* sources */
+} /* impl LoadedSources */
+
+impl ProgramFiles {
+    // Files are polled for changes since this call
+    pub fn new(directory: impl Into<PathBuf>, name: &str) -> ProgramFiles {
+        let mut files = ProgramFiles { directory: directory.into(), name: name.to_string(), modified: Vec::new() };
+        files.modified = files.modification_times();
+        files
+    } /* new */
+
+    pub fn path(&self, stage: ShaderStage) -> PathBuf {
+        self.directory.join(format!("{}.{}", self.name, stage.extension()))
+    } /* path */
+
+    fn modification_times(&self) -> Vec<(ShaderStage, Option<SystemTime>)> {
+        [ShaderStage::Vertex, ShaderStage::Geometry, ShaderStage::Fragment]
+            .into_iter()
+            .map(|stage| (stage, std::fs::metadata(self.path(stage)).and_then(|metadata| metadata.modified()).ok()))
+            .collect()
+    } /* modification_times */
+
+    // Whether any file was changed, created or removed since last poll; any other time counts, so restored backups are noticed too
+    pub fn poll_changed(&mut self) -> bool {
+        let modified = self.modification_times();
+        let changed = modified != self.modified;
+        self.modified = modified;
+        changed
+    } /* poll_changed */
+
+    // Geometry shader file is optional
+    pub fn load(&self) -> Result<LoadedSources, ShaderError> {
+        let read = |stage: ShaderStage| match std::fs::read_to_string(self.path(stage)) {
+            Ok(source) => Ok(Some(source)),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(ShaderError::from(error)),
+        };
+        let required = |stage: ShaderStage| read(stage)?.ok_or(ShaderError::MissingStage(stage));
+
+        Ok(LoadedSources {
+            vertex: required(ShaderStage::Vertex)?,
+            geometry: read(ShaderStage::Geometry)?,
+            fragment: required(ShaderStage::Fragment)?,
+        })
+    } /* load */
+} /* impl ProgramFiles */
+
+impl std::fmt::Display for ShaderStage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+} /* impl std::fmt::Display for ShaderStage */
+
+impl std::fmt::Display for ShaderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ShaderError::Io(error) => write!(f, "shader file I/O error: {error}"),
+            ShaderError::Compile { stage, log } => write!(f, "{stage} shader compilation failed:\n{log}"),
+            ShaderError::Link { log } => write!(f, "shader program linking failed:\n{log}"),
+            ShaderError::MissingStage(stage) => write!(f, "shader program has no {stage} shader"),
+        }
+    }
+} /* impl std::fmt::Display for ShaderError */
+
+impl std::error::Error for ShaderError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ShaderError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+} /* impl std::error::Error for ShaderError */
+
+impl From<std::io::Error> for ShaderError {
+    fn from(error: std::io::Error) -> ShaderError {
+        ShaderError::Io(error)
+    }
+}
+
+// Source line number (1-based) log line refers to, known formats are
+// "0:12(5): error: ..." (Mesa), "0(12) : error C0000: ..." (NVIDIA) and "ERROR: 0:12: ..." (AMD, Intel)
+pub fn log_line_number(log_line: &str) -> Option<usize> {
+    let location = log_line.trim_start();
+    let location = ["ERROR:", "WARNING:"].iter()
+        .find_map(|prefix| location.strip_prefix(prefix))
+        .unwrap_or(location)
+        .trim_start();
+
+    // Source string index goes first, it is always 0 as every stage is single string
+    let rest = location.trim_start_matches(|c: char| c.is_ascii_digit());
+    if rest.len() == location.len() {
+        return None;
+    }
+    let rest = rest.strip_prefix(':').or_else(|| rest.strip_prefix('('))?;
+    let digits = rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    rest[..digits].parse().ok()
+} /* log_line_number */
+
+// Driver log with source line every log line refers to quoted under it
+pub fn annotate_log(log: &str, source: &str) -> String {
+    let source_lines: Vec<&str> = source.lines().collect();
+    let mut annotated = String::with_capacity(log.len() * 2);
+
+    for log_line in log.trim_end_matches(['\0', '\n', '\r', ' ']).lines() {
+        annotated.push_str(log_line);
+        annotated.push('\n');
+        let quoted = log_line_number(log_line)
+            .filter(|&number| number > 0)
+            .and_then(|number| source_lines.get(number - 1).map(|line| (number, line.trim())));
+        if let Some((number, line)) = quoted {
+            annotated.push_str(&format!("{number:>5} | {line}\n"));
+        }
+    }
+    annotated
+} /* annotate_log */
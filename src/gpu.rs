@@ -0,0 +1,145 @@
+// Owning wrappers for GL vertex buffers and vertex arrays, and safe calls for frame wide GL state.
+// GL context must be current and loaded while any of them exists, they are deleted on drop.
+
+use pixel_mobs_gun_rs::linmath;
+
+// Plain vertex data that is copied to GPU as is.
+// Safety: implementor must be #[repr(C)] of exactly COMPONENTS f32 values without padding,
+// buffers read and GL draws that many floats per vertex.
+#[allow(clippy::missing_safety_doc)] // Safety is documented above, crate has no doc comments
+pub unsafe trait Vertex: Copy {
+    const COMPONENTS: i32;
+} /* Vertex */
+
+// Vec2 is #[repr(C)] of x and y
+unsafe impl Vertex for linmath::Vec2<f32> {
+    const COMPONENTS: i32 = 2;
+}
+
+pub fn viewport(width: i32, height: i32) {
+    unsafe {
+        gl::Viewport(0, 0, width, height);
+    }
+} /* viewport */
+
+pub fn clear(color: [f32; 3]) {
+    unsafe {
+        gl::ClearColor(color[0], color[1], color[2], 1.0);
+        gl::Clear(gl::COLOR_BUFFER_BIT);
+    }
+} /* clear */
+
+// Wait until all issued commands are done
+pub fn finish() {
+    unsafe {
+        gl::Finish();
+    }
+} /* finish */
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Usage {
+    // Uploaded once
+    Static,
+    // Updated every frame
+    Dynamic,
+} /* Usage */
+
+// GL array buffer, its size in bytes is tracked to keep updates in bounds
+pub struct Buffer {
+    id: u32,
+    size: usize,
+} /* Buffer */
+
+pub struct VertexArray {
+    id: u32,
+} /* VertexArray */
+
+impl Usage {
+    fn gl_usage(self) -> gl::types::GLenum {
+        match self {
+            Usage::Static => gl::STATIC_DRAW,
+            Usage::Dynamic => gl::DYNAMIC_DRAW,
+        }
+    }
+} /* impl Usage */
+
+impl Buffer {
+    pub fn new() -> Buffer {
+        let mut id: u32 = 0;
+        unsafe {
+            gl::GenBuffers(1, &mut id);
+        }
+        Buffer { id, size: 0 }
+    } /* new */
+
+    // Buffer with space for capacity vertices, contents undefined
+    pub fn with_capacity<T: Vertex>(capacity: usize, usage: Usage) -> Buffer {
+        let mut buffer = Buffer::new();
+        buffer.size = capacity * std::mem::size_of::<T>();
+        unsafe {
+            gl::BindBuffer(gl::ARRAY_BUFFER, buffer.id);
+            gl::BufferData(gl::ARRAY_BUFFER, buffer.size as isize, std::ptr::null(), usage.gl_usage());
+        }
+        buffer
+    } /* with_capacity */
+
+    // Replace whole buffer by data, buffer is resized to fit it
+    pub fn upload<T: Vertex>(&mut self, data: &[T], usage: Usage) {
+        self.size = std::mem::size_of_val(data);
+        unsafe {
+            gl::BindBuffer(gl::ARRAY_BUFFER, self.id);
+            gl::BufferData(gl::ARRAY_BUFFER, self.size as isize, data.as_ptr().cast(), usage.gl_usage());
+        }
+    } /* upload */
+
+    // Overwrite part of buffer starting from vertex index, data must fit into buffer
+    pub fn update<T: Vertex>(&self, first: usize, data: &[T]) {
+        let offset = first * std::mem::size_of::<T>();
+        let size = std::mem::size_of_val(data);
+        assert!(offset + size <= self.size, "buffer update out of bounds: {} bytes at {offset}, buffer is {} bytes", size, self.size);
+
+        unsafe {
+            gl::BindBuffer(gl::ARRAY_BUFFER, self.id);
+            gl::BufferSubData(gl::ARRAY_BUFFER, offset as isize, size as isize, data.as_ptr().cast());
+        }
+    } /* update */
+} /* impl Buffer */
+
+impl Drop for Buffer {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteBuffers(1, &self.id);
+        }
+    }
+} /* impl Drop for Buffer */
+
+impl VertexArray {
+    // Vertex array reading attribute 0 from buffer as T vertices
+    pub fn new<T: Vertex>(buffer: &Buffer) -> VertexArray {
+        let mut id: u32 = 0;
+        unsafe {
+            gl::GenVertexArrays(1, &mut id);
+            gl::BindVertexArray(id);
+            gl::EnableVertexAttribArray(0);
+            gl::VertexAttribFormat(0, T::COMPONENTS, gl::FLOAT, gl::FALSE, 0);
+            gl::BindVertexBuffer(0, buffer.id, 0, std::mem::size_of::<T>() as i32);
+        }
+        VertexArray { id }
+    } /* new */
+
+    // Draw count vertices from first as points
+    pub fn draw_points(&self, first: usize, count: usize) {
+        unsafe {
+            gl::BindVertexArray(self.id);
+            gl::DrawArrays(gl::POINTS, first as i32, count as i32);
+        }
+    } /* draw_points */
+} /* impl VertexArray */
+
+impl Drop for VertexArray {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteVertexArrays(1, &self.id);
+        }
+    }
+} /* impl Drop for VertexArray */
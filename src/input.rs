@@ -0,0 +1,333 @@
+/* Action based input: device inputs are bound to actions (see assets/bindings.txt), front end feeds input changes in, engine gets PlayerInput snapshot.
+ * Keyboard, mouse and gamepad all go through it, engine doesn't know where input came from. */
+
+use crate::game::{PlayerInput, Vec2};
+use crate::view::View;
+use crate::weapon::WEAPON_COUNT;
+
+// Bindings compiled into the game, used when no other ones are given
+pub const DEFAULT_BINDINGS: &str = include_str!("../assets/bindings.txt");
+
+// Analog inputs (sticks, triggers) count as held beyond this value
+pub const ANALOG_PRESS_THRESHOLD: f32 = 0.5;
+// Stick deflection ignored as noise of resting stick
+pub const STICK_DEADZONE: f32 = 0.2;
+// Stick aim points this far from player in aimed direction
+pub const STICK_AIM_DISTANCE: f32 = 0.5;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Action {
+    MoveX,
+    MoveY,
+    Fire,
+    // Pointer position player aims at
+    Aim,
+    // Aim direction axes relative to player, e.g. right stick
+    AimX,
+    AimY,
+    Reload,
+    Pause,
+    // Weapon slot, from 0
+    SwitchWeapon(usize),
+} /* Action */
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Binding {
+    pub action: Action,
+    // Normalized input name (see normalize_input_name)
+    pub input: String,
+    // Axis direction for axis actions, 1 for others
+    pub value: f32,
+} /* Binding */
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct InputMap {
+    pub bindings: Vec<Binding>,
+} /* InputMap */
+
+#[derive(Debug)]
+pub enum InputMapError {
+    Io(std::io::Error),
+    Parse { line: usize, message: String },
+} /* InputMapError */
+
+// Current input state, built from input changes front end reports
+#[derive(Clone, Debug)]
+pub struct InputState {
+    map: InputMap,
+    // Inputs currently held down
+    held: Vec<String>,
+    // Last values of analog inputs, in [-1, 1]
+    analog: Vec<(String, f32)>,
+    // Actions pressed since they were last consumed
+    pressed: Vec<Action>,
+    // Pointer position on screen, in pixels; converted to world when used, as view may move
+    pointer: Vec2,
+    // Last aim stick direction, used instead of pointer until pointer moves again
+    aim_direction: Option<Vec2>,
+} /* InputState */
+
+impl std::fmt::Display for InputMapError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InputMapError::Io(error) => write!(f, "input bindings I/O error: {error}"),
+            InputMapError::Parse { line, message } => write!(f, "input bindings line {line}: {message}"),
+        }
+    }
+} /* impl std::fmt::Display for InputMapError */
+
+impl std::error::Error for InputMapError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            InputMapError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+} /* impl std::error::Error for InputMapError */
+
+impl From<std::io::Error> for InputMapError {
+    fn from(error: std::io::Error) -> InputMapError {
+        InputMapError::Io(error)
+    }
+}
+
+// Input names are compared ignoring case and spaces, so `Left Shift`, `LeftShift` and `leftshift` match
+pub fn normalize_input_name(name: &str) -> String {
+    name.chars().filter(|character| !character.is_whitespace()).flat_map(char::to_lowercase).collect()
+} /* normalize_input_name */
+
+// Radial deadzone, rest of stick range is rescaled to start from 0 at deadzone edge
+pub fn apply_deadzone(stick: Vec2, deadzone: f32) -> Vec2 {
+    let length = stick.length();
+    if length <= deadzone {
+        return Vec2::new(0.0, 0.0);
+    }
+    let scaled = ((length - deadzone) / (1.0 - deadzone)).min(1.0);
+    stick * (scaled / length)
+} /* apply_deadzone */
+
+impl Action {
+    pub fn is_axis(self) -> bool {
+        matches!(self, Action::MoveX | Action::MoveY | Action::AimX | Action::AimY)
+    } /* is_axis */
+} /* impl Action */
+
+impl InputMap {
+    pub fn new() -> InputMap {
+        InputMap { bindings: Vec::new() }
+    } /* new */
+
+    pub fn bind(&mut self, action: Action, input: &str, value: f32) {
+        self.bindings.push(Binding { action, input: normalize_input_name(input), value });
+    } /* bind */
+
+    // Parse bindings: `action input [value]` per line, `#` starts comment.
+    // Value is axis direction for move_x/move_y/aim_x/aim_y (1 by default) and weapon slot from 1 for switch_weapon.
+    pub fn parse(source: &str) -> Result<InputMap, InputMapError> {
+        let mut map = InputMap::new();
+
+        for (line_index, line) in source.lines().enumerate() {
+            let error = |message: String| InputMapError::Parse { line: line_index + 1, message };
+
+            let content = line.split('#').next().unwrap_or("");
+            let fields: Vec<&str> = content.split_whitespace().collect();
+            if fields.is_empty() {
+                continue;
+            }
+            let (action, input, value) = match fields[..] {
+                [action, input] => (action, input, None),
+                [action, input, value] => (action, input, Some(value)),
+                _ => return Err(error(format!("expected 2 or 3 fields (action input [value]), got {}", fields.len()))),
+            };
+
+            let (action, value) = match (action, value) {
+                ("move_x" | "move_y" | "aim_x" | "aim_y", value) => {
+                    let axis = match action {
+                        "move_x" => Action::MoveX,
+                        "move_y" => Action::MoveY,
+                        "aim_x" => Action::AimX,
+                        _ => Action::AimY,
+                    };
+                    let value = match value {
+                        Some(value) => value.parse().ok().filter(|value: &f32| (-1.0..=1.0).contains(value))
+                            .ok_or_else(|| error(format!("invalid axis value '{value}', expected -1 to 1")))?,
+                        None => 1.0,
+                    };
+                    (axis, value)
+                }
+                ("switch_weapon", Some(slot)) => {
+                    let slot: usize = slot.parse().ok().filter(|slot| (1..=WEAPON_COUNT).contains(slot))
+                        .ok_or_else(|| error(format!("invalid weapon slot '{slot}', expected 1 to {WEAPON_COUNT}")))?;
+                    (Action::SwitchWeapon(slot - 1), 1.0)
+                }
+                ("switch_weapon", None) => return Err(error("weapon slot expected".to_string())),
+                ("fire" | "aim" | "reload" | "pause", Some(value)) => {
+                    return Err(error(format!("action '{action}' takes no value, got '{value}'")));
+                }
+                ("fire", None) => (Action::Fire, 1.0),
+                ("aim", None) => (Action::Aim, 1.0),
+                ("reload", None) => (Action::Reload, 1.0),
+                ("pause", None) => (Action::Pause, 1.0),
+                _ => return Err(error(format!("unknown action '{action}'"))),
+            };
+
+            map.bind(action, input, value);
+        }
+
+        Ok(map)
+    } /* parse */
+
+    pub fn load(path: impl AsRef<std::path::Path>) -> Result<InputMap, InputMapError> {
+        InputMap::parse(&std::fs::read_to_string(path)?)
+    } /* load */
+
+    // WASD and arrows move, space and left mouse button fire, mouse aims
+    pub fn default_map() -> InputMap {
+        InputMap::parse(DEFAULT_BINDINGS).expect("Built-in bindings must be valid")
+    } /* default_map */
+
+    // Bindings of given input, name is normalized
+    pub fn bindings_of<'a>(&'a self, input: &'a str) -> impl Iterator<Item = &'a Binding> + 'a {
+        self.bindings.iter().filter(move |binding| binding.input == input)
+    } /* bindings_of */
+} /* impl InputMap */
+
+impl Default for InputMap {
+    fn default() -> InputMap {
+        InputMap::new()
+    }
+} /* impl Default for InputMap */
+
+impl InputState {
+    pub fn new(map: InputMap) -> InputState {
+        InputState { map, held: Vec::new(), analog: Vec::new(), pressed: Vec::new(), pointer: Vec2::new(0.0, 0.0), aim_direction: None }
+    } /* new */
+
+    pub fn map(&self) -> &InputMap {
+        &self.map
+    } /* map */
+
+    // Button or key went down, repeats while it is held are ignored
+    pub fn press(&mut self, input: &str) {
+        let input = normalize_input_name(input);
+        if self.held.contains(&input) {
+            return;
+        }
+        for binding in self.map.bindings_of(&input) {
+            if !binding.action.is_axis() {
+                self.pressed.push(binding.action);
+            }
+        }
+        self.held.push(input);
+    } /* press */
+
+    pub fn release(&mut self, input: &str) {
+        let input = normalize_input_name(input);
+        self.held.retain(|held| *held != input);
+    } /* release */
+
+    // Everything is released, e.g. when window loses focus
+    pub fn release_all(&mut self) {
+        self.held.clear();
+        self.analog.clear();
+    } /* release_all */
+
+    // Inputs of one device are released and its analog inputs reset, e.g. `pad_` ones when gamepad is unplugged
+    pub fn release_device(&mut self, prefix: &str) {
+        let prefix = normalize_input_name(prefix);
+        self.held.retain(|held| !held.starts_with(&prefix));
+        self.analog.retain(|(analog, _)| !analog.starts_with(&prefix));
+    } /* release_device */
+
+    // Analog input moved; it drives axes directly and acts as button beyond ANALOG_PRESS_THRESHOLD
+    pub fn set_analog(&mut self, input: &str, value: f32) {
+        let name = normalize_input_name(input);
+        let value = value.clamp(-1.0, 1.0);
+        match self.analog.iter_mut().find(|(analog, _)| *analog == name) {
+            Some(analog) => analog.1 = value,
+            None => self.analog.push((name.clone(), value)),
+        }
+
+        if value.abs() >= ANALOG_PRESS_THRESHOLD {
+            self.press(&name);
+        } else {
+            self.release(&name);
+        }
+
+        let aims = self.map.bindings_of(&name).any(|binding| matches!(binding.action, Action::AimX | Action::AimY));
+        let direction = Vec2::new(self.axis(Action::AimX), self.axis(Action::AimY));
+        if aims && direction.length2() > 0.0 {
+            self.aim_direction = Some(direction.normalized());
+        }
+    } /* set_analog */
+
+    // Pointer input moved to screen position, it is aimed at if bound to Aim
+    pub fn point(&mut self, input: &str, screen_position: Vec2) {
+        let input = normalize_input_name(input);
+        if self.map.bindings_of(&input).any(|binding| binding.action == Action::Aim) {
+            self.pointer = screen_position;
+            self.aim_direction = None;
+        }
+    } /* point */
+
+    // World point player aims at: along last aim stick direction, or under pointer
+    pub fn aim(&self, player_position: Vec2, view: &View) -> Vec2 {
+        match self.aim_direction {
+            Some(direction) => player_position + direction * STICK_AIM_DISTANCE,
+            None => view.screen_to_world(self.pointer),
+        }
+    } /* aim */
+
+    // Analog value of input, digital ones are 1 while held
+    fn value(&self, input: &str) -> f32 {
+        match self.analog.iter().find(|(analog, _)| analog == input) {
+            Some((_, value)) => *value,
+            None if self.held.iter().any(|held| held == input) => 1.0,
+            None => 0.0,
+        }
+    } /* value */
+
+    pub fn is_held(&self, action: Action) -> bool {
+        self.map.bindings.iter().any(|binding| binding.action == action && self.held.contains(&binding.input))
+    } /* is_held */
+
+    // Sum of bindings of axis scaled by their inputs, clamped to [-1, 1]
+    pub fn axis(&self, action: Action) -> f32 {
+        self.map.bindings.iter()
+            .filter(|binding| binding.action == action)
+            .map(|binding| binding.value * self.value(&binding.input))
+            .sum::<f32>()
+            .clamp(-1.0, 1.0)
+    } /* axis */
+
+    pub fn was_pressed(&self, action: Action) -> bool {
+        self.pressed.contains(&action)
+    } /* was_pressed */
+
+    // Was action pressed, press is consumed
+    pub fn take_pressed(&mut self, action: Action) -> bool {
+        let count = self.pressed.len();
+        self.pressed.retain(|pressed| *pressed != action);
+        self.pressed.len() != count
+    } /* take_pressed */
+
+    // Engine input for next tick; presses reach it until end_tick, even if frame runs no ticks
+    pub fn player_input(&self, player_position: Vec2, view: &View) -> PlayerInput {
+        PlayerInput {
+            move_axis: Vec2::new(self.axis(Action::MoveX), self.axis(Action::MoveY)),
+            aim: self.aim(player_position, view),
+            // Short tap between ticks still fires
+            fire: self.is_held(Action::Fire) || self.was_pressed(Action::Fire),
+            reload: self.was_pressed(Action::Reload),
+            switch_weapon: self.pressed.iter().rev().find_map(|action| match action {
+                Action::SwitchWeapon(slot) => Some(*slot),
+                _ => None,
+            }),
+        }
+    } /* player_input */
+
+    // Tick consumed presses of its actions
+    pub fn end_tick(&mut self) {
+        self.pressed.retain(|action| !matches!(action, Action::Fire | Action::Reload | Action::SwitchWeapon(_)));
+    } /* end_tick */
+} /* impl InputState */
@@ -0,0 +1,312 @@
+/* Tile map levels: walls, player start, enemy spawn points and pickups (see assets/level.txt) */
+
+use crate::arena::Arena;
+use crate::collision::Aabb;
+use crate::game::Vec2;
+use crate::pickup::PickupKind;
+
+// Level used when no other one is given
+pub const DEFAULT_LEVEL: &str = include_str!("../assets/level.txt");
+pub const DEFAULT_TILE_SIZE: f32 = 0.1;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Tile {
+    Floor,
+    Wall,
+} /* Tile */
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Level {
+    // Size in tiles
+    pub width: usize,
+    pub height: usize,
+    // Tile width and height in world units
+    pub tile_size: f32,
+    // Row by row, top row first
+    pub tiles: Vec<Tile>,
+    pub player_start: Vec2,
+    pub spawn_points: Vec<Vec2>,
+    pub pickups: Vec<(PickupKind, Vec2)>,
+} /* Level */
+
+#[derive(Debug)]
+pub enum LevelError {
+    Io(std::io::Error),
+    Parse { line: usize, message: String },
+} /* LevelError */
+
+impl std::fmt::Display for LevelError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LevelError::Io(error) => write!(f, "level I/O error: {error}"),
+            LevelError::Parse { line, message } => write!(f, "level line {line}: {message}"),
+        }
+    }
+} /* impl std::fmt::Display for LevelError */
+
+impl std::error::Error for LevelError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            LevelError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+} /* impl std::error::Error for LevelError */
+
+impl From<std::io::Error> for LevelError {
+    fn from(error: std::io::Error) -> LevelError {
+        LevelError::Io(error)
+    }
+}
+
+impl Level {
+    // Format: optional `name value` property lines (only `tile_size` for now), then map rows.
+    // Map: `#` wall, `.` floor, `P` player start, `E` enemy spawn point,
+    // `H` health, `A` ammo, `W` weapon boost, `S` speed boost pickups. `;` starts comment.
+    pub fn parse(source: &str) -> Result<Level, LevelError> {
+        let mut tile_size = DEFAULT_TILE_SIZE;
+        // (line number, row)
+        let mut rows = Vec::<(usize, &str)>::new();
+
+        for (line_index, line) in source.lines().enumerate() {
+            let error = |message: String| LevelError::Parse { line: line_index + 1, message };
+            let content = line.split(';').next().unwrap_or("").trim_end();
+            if content.is_empty() {
+                continue;
+            }
+
+            if content.starts_with(|c: char| c.is_ascii_lowercase()) {
+                if !rows.is_empty() {
+                    return Err(error("properties must go before map".to_string()));
+                }
+                let Some(("tile_size", value)) = content.split_once(' ') else {
+                    return Err(error(format!("unknown property '{content}'")));
+                };
+                tile_size = value.trim().parse().ok().filter(|size: &f32| *size > 0.0)
+                    .ok_or_else(|| error(format!("invalid tile size '{}'", value.trim())))?;
+                continue;
+            }
+            rows.push((line_index + 1, content));
+        }
+
+        let Some(width) = rows.first().map(|(_, row)| row.chars().count()) else {
+            return Err(LevelError::Parse { line: source.lines().count(), message: "level has no map".to_string() });
+        };
+        let height = rows.len();
+        let mut level = Level {
+            width,
+            height,
+            tile_size,
+            tiles: Vec::with_capacity(width * height),
+            player_start: Vec2::new(0.0, 0.0),
+            spawn_points: Vec::new(),
+            pickups: Vec::new(),
+        };
+        let mut player_start = None;
+
+        for (row_index, (line, row)) in rows.iter().enumerate() {
+            let error = |message: String| LevelError::Parse { line: *line, message };
+            if row.chars().count() != width {
+                return Err(error(format!("row is {} tiles wide, map is {width}", row.chars().count())));
+            }
+
+            for (column, character) in row.chars().enumerate() {
+                let center = level.tile_center(column, row_index);
+                let tile = match character {
+                    '#' => Tile::Wall,
+                    '.' => Tile::Floor,
+                    'P' if player_start.is_some() => return Err(error("second player start".to_string())),
+                    'P' => {
+                        player_start = Some(center);
+                        Tile::Floor
+                    }
+                    'E' => {
+                        level.spawn_points.push(center);
+                        Tile::Floor
+                    }
+                    'H' | 'A' | 'W' | 'S' => {
+                        let kind = match character {
+                            'H' => PickupKind::Health,
+                            'A' => PickupKind::Ammo,
+                            'W' => PickupKind::WeaponBoost,
+                            _ => PickupKind::SpeedBoost,
+                        };
+                        level.pickups.push((kind, center));
+                        Tile::Floor
+                    }
+                    _ => return Err(error(format!("unknown tile '{character}'"))),
+                };
+                level.tiles.push(tile);
+            }
+        }
+
+        level.player_start = player_start.ok_or_else(|| LevelError::Parse { line: rows[0].0, message: "no player start".to_string() })?;
+        Ok(level)
+    } /* parse */
+
+    pub fn load(path: impl AsRef<std::path::Path>) -> Result<Level, LevelError> {
+        Level::parse(&std::fs::read_to_string(path)?)
+    } /* load */
+
+    // Level in parse() format, parsed level parses back to equal one
+    pub fn to_source(&self) -> String {
+        let mut rows: Vec<Vec<char>> = self.tiles
+            .chunks(self.width)
+            .map(|row| row.iter().map(|tile| if *tile == Tile::Wall { '#' } else { '.' }).collect())
+            .collect();
+        let mut mark = |position: Vec2, marker: char| {
+            if let Some((column, row)) = self.tile_coords(position) {
+                rows[row][column] = marker;
+            }
+        };
+
+        mark(self.player_start, 'P');
+        for &spawn_point in &self.spawn_points {
+            mark(spawn_point, 'E');
+        }
+        for &(kind, position) in &self.pickups {
+            let marker = match kind {
+                PickupKind::Health => 'H',
+                PickupKind::Ammo => 'A',
+                PickupKind::WeaponBoost => 'W',
+                PickupKind::SpeedBoost => 'S',
+            };
+            mark(position, marker);
+        }
+
+        let mut source = format!("tile_size {}\n", self.tile_size);
+        for row in rows {
+            source.extend(row);
+            source.push('\n');
+        }
+        source
+    } /* to_source */
+
+    pub fn default_level() -> Level {
+        Level::parse(DEFAULT_LEVEL).expect("Built-in level must be valid")
+    } /* default_level */
+
+    // Whole map, centered on origin
+    pub fn bounds(&self) -> Aabb {
+        let half_size = Vec2::new(self.width as f32, self.height as f32) * (self.tile_size * 0.5);
+        Aabb::from_center(Vec2::new(0.0, 0.0), half_size)
+    } /* bounds */
+
+    pub fn tile(&self, column: usize, row: usize) -> Option<Tile> {
+        if column >= self.width || row >= self.height {
+            return None;
+        }
+        Some(self.tiles[row * self.width + column])
+    } /* tile */
+
+    pub fn tile_center(&self, column: usize, row: usize) -> Vec2 {
+        let bounds = self.bounds();
+        Vec2::new(
+            bounds.min.x + (column as f32 + 0.5) * self.tile_size,
+            bounds.max.y - (row as f32 + 0.5) * self.tile_size,
+        )
+    } /* tile_center */
+
+    pub fn tile_rect(&self, column: usize, row: usize) -> Aabb {
+        Aabb::from_center(self.tile_center(column, row), Vec2::new(self.tile_size, self.tile_size) * 0.5)
+    } /* tile_rect */
+
+    // Column and row of tile under point, None outside map
+    pub fn tile_coords(&self, position: Vec2) -> Option<(usize, usize)> {
+        let bounds = self.bounds();
+        let column = ((position.x - bounds.min.x) / self.tile_size).floor();
+        let row = ((bounds.max.y - position.y) / self.tile_size).floor();
+
+        if column < 0.0 || row < 0.0 || column >= self.width as f32 || row >= self.height as f32 {
+            return None;
+        }
+        Some((column as usize, row as usize))
+    } /* tile_coords */
+
+    // Tile under point, everything outside map is wall
+    pub fn tile_at(&self, position: Vec2) -> Tile {
+        self.tile_coords(position)
+            .and_then(|(column, row)| self.tile(column, row))
+            .unwrap_or(Tile::Wall)
+    } /* tile_at */
+
+    pub fn is_wall(&self, position: Vec2) -> bool {
+        self.tile_at(position) == Tile::Wall
+    } /* is_wall */
+
+    // Does segment cross only floor tiles, walks tiles along it
+    pub fn line_of_sight(&self, from: Vec2, to: Vec2) -> bool {
+        let Some((mut column, mut row)) = self.tile_coords(from) else {
+            return false;
+        };
+        let bounds = self.bounds();
+        let delta = to - from;
+        let step_column: isize = if delta.x > 0.0 { 1 } else { -1 };
+        let step_row: isize = if delta.y < 0.0 { 1 } else { -1 };
+
+        // Distance along segment (in its fractions) to next column/row border, and between borders
+        let next_x = bounds.min.x + (column as f32 + if delta.x > 0.0 { 1.0 } else { 0.0 }) * self.tile_size;
+        let next_y = bounds.max.y - (row as f32 + if delta.y < 0.0 { 1.0 } else { 0.0 }) * self.tile_size;
+        let mut time_x = if delta.x != 0.0 { (next_x - from.x) / delta.x } else { f32::INFINITY };
+        let mut time_y = if delta.y != 0.0 { (next_y - from.y) / delta.y } else { f32::INFINITY };
+        let step_time_x = if delta.x != 0.0 { self.tile_size / delta.x.abs() } else { f32::INFINITY };
+        let step_time_y = if delta.y != 0.0 { self.tile_size / delta.y.abs() } else { f32::INFINITY };
+
+        loop {
+            if self.tile(column, row) != Some(Tile::Floor) {
+                return false;
+            }
+            if time_x > 1.0 && time_y > 1.0 {
+                return true;
+            }
+
+            if time_x < time_y {
+                let Some(next) = column.checked_add_signed(step_column) else { return false };
+                column = next;
+                time_x += step_time_x;
+            } else {
+                let Some(next) = row.checked_add_signed(step_row) else { return false };
+                row = next;
+                time_y += step_time_y;
+            }
+        }
+    } /* line_of_sight */
+
+    // Walls merged into as few rectangles as is easy: horizontal runs, then equal runs stacked vertically
+    pub fn wall_rects(&self) -> Vec<Aabb> {
+        // (first column, last column, first row, last row)
+        let mut rects = Vec::<(usize, usize, usize, usize)>::new();
+
+        for row in 0..self.height {
+            let mut column = 0;
+            while column < self.width {
+                if self.tile(column, row) != Some(Tile::Wall) {
+                    column += 1;
+                    continue;
+                }
+                let first = column;
+                while self.tile(column + 1, row) == Some(Tile::Wall) {
+                    column += 1;
+                }
+
+                match rects.iter_mut().find(|rect| rect.0 == first && rect.1 == column && rect.3 + 1 == row) {
+                    Some(rect) => rect.3 = row,
+                    None => rects.push((first, column, row, row)),
+                }
+                column += 1;
+            }
+        }
+
+        rects.iter()
+            .map(|&(first_column, last_column, first_row, last_row)| {
+                let top_left = self.tile_rect(first_column, first_row);
+                let bottom_right = self.tile_rect(last_column, last_row);
+                Aabb::new(Vec2::new(top_left.min.x, bottom_right.min.y), Vec2::new(bottom_right.max.x, top_left.max.y))
+            })
+            .collect()
+    } /* wall_rects */
+
+    pub fn arena(&self) -> Arena {
+        Arena::with_obstacles(self.bounds(), self.wall_rects())
+    } /* arena */
+} /* impl Level */
@@ -0,0 +1,23 @@
+/* Headless game library: simulation, math and timing, no SDL2/OpenGL dependency */
+
+pub mod linmath;
+pub mod arena;
+pub mod camera;
+pub mod collision;
+pub mod pool;
+pub mod rng;
+pub mod score;
+pub mod enemy;
+pub mod game;
+pub mod glsl;
+pub mod input;
+pub mod level;
+pub mod nav;
+pub mod pickup;
+pub mod replay;
+pub mod spatial;
+pub mod spawner;
+pub mod steering;
+pub mod timer;
+pub mod view;
+pub mod weapon;
@@ -2,7 +2,7 @@
 
 macro_rules! vecn_declare_struct {
     ($type_name: ident, $($x: ident),* ) => {
-        #[repr(align(4))]
+        #[repr(C, align(4))]
         pub struct $type_name<T> {
             $( pub $x : T, )*
         }
@@ -24,6 +24,18 @@ macro_rules! vecn_impl_new_copy_clone {
 
         impl<T: Copy> Copy for $type<T> {
         }
+
+        impl<T: PartialEq> PartialEq for $type<T> {
+            fn eq(&self, rhs: &$type<T>) -> bool {
+                true $(&& self.$x == rhs.$x)*
+            }
+        }
+
+        impl<T: core::fmt::Debug> core::fmt::Debug for $type<T> {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                f.debug_struct(stringify!($type))$(.field(stringify!($x), &self.$x))*.finish()
+            }
+        }
     }
 }
 
@@ -1,66 +1,194 @@
-// Shader for all entities exist in game
-// uniforms: point_size: vec2, projection_size: vec2, point_color: vec3
+// Shader for all entities exist in game, see assets/shaders/entity.*
+// uniforms: point_size: vec2, view_projection: mat4, point_color: vec3
 mod entity_shader {
-    pub const FRAG: &'static str = r#"
-    #version 330 core
-
-    layout(location = 0) in vec2 position;
-
-    uniform vec3 point_color;
-    uniform vec2 projection_size;
-
-    out vec3 color;
-
-    void main(void) {
-        color = point_color;
-        gl_Position = vec4(position / projection_size, 0, 1);
-    }
-    "#;
+    use crate::shader::{Program, Uniform, UniformValue};
+    use pixel_mobs_gun_rs::glsl::ProgramSources;
+    use pixel_mobs_gun_rs::linmath;
+
+    pub const NAME: &str = "entity";
+
+    pub const SOURCES: ProgramSources = ProgramSources {
+        vertex: include_str!("../assets/shaders/entity.vert"),
+        geometry: Some(include_str!("../assets/shaders/entity.geom")),
+        fragment: include_str!("../assets/shaders/entity.frag"),
+    };
+
+    // Program with its uniforms, they are looked up again for every reloaded program
+    pub struct EntityShader {
+        pub program: Program,
+        pub point_size: Uniform<linmath::Vec2<f32>>,
+        pub point_color: Uniform<[f32; 3]>,
+        pub view_projection: Uniform<[f32; 16]>,
+    } /* EntityShader */
+
+    impl EntityShader {
+        pub fn new(program: Program) -> EntityShader {
+            EntityShader {
+                point_size: uniform(&program, c"point_size"),
+                point_color: uniform(&program, c"point_color"),
+                view_projection: uniform(&program, c"view_projection"),
+                program,
+            }
+        } /* new */
+    } /* impl EntityShader */
+
+    // Shader being edited may not use all uniforms, missing one is reported and ignored
+    fn uniform<T: UniformValue>(program: &Program, name: &std::ffi::CStr) -> Uniform<T> {
+        program.uniform(name).unwrap_or_else(|| {
+            println!("Entity shader uniform {name:?} not found");
+            Uniform::inactive(program)
+        })
+    } /* uniform */
+} /* mod entity_shader */
 
-    pub const GEOM: &'static str = r#"
-    #version 330 core
+mod gpu;
+mod shader;
 
-    layout(points) in;
-    layout(triangle_strip, max_vertices = 4) out;
+use pixel_mobs_gun_rs::{camera, enemy, game, glsl, input, level, linmath, pickup, replay, score, spawner, timer, view};
 
-    uniform vec2 point_size;
-    uniform vec2 projection_size;
-    in vec3 color[];
+type Vec2 = linmath::Vec2<f32>;
 
-    out vec3 gs_color;
+// Timer clock backed by SDL performance counter
+struct SdlClock {
+    sdl_timer: sdl2::TimerSubsystem,
+} /* SdlClock */
 
-    void main(void) {
-        vec2 size = point_size / projection_size / 2;
-        gl_Position = gl_in[0].gl_Position; gl_Position.xy += size * vec2(-1, -1); gs_color = color[0]; EmitVertex();
-        gl_Position = gl_in[0].gl_Position; gl_Position.xy += size * vec2(-1, +1); gs_color = color[0]; EmitVertex();
-        gl_Position = gl_in[0].gl_Position; gl_Position.xy += size * vec2(+1, -1); gs_color = color[0]; EmitVertex();
-        gl_Position = gl_in[0].gl_Position; gl_Position.xy += size * vec2(+1, +1); gs_color = color[0]; EmitVertex();
-        EndPrimitive();
+impl timer::Clock for SdlClock {
+    fn ticks(&self) -> u64 {
+        self.sdl_timer.performance_counter()
     }
-    "#;
-
-    pub const VERT: &'static str = r#"
-    #version 330 core
-    layout(location = 0) out vec4 out_color;
 
-    in vec3 gs_color;
+    fn ticks_per_second(&self) -> u64 {
+        self.sdl_timer.performance_frequency()
+    }
+} /* impl timer::Clock for SdlClock */
+
+// Replay of the last played game is saved here on exit
+const LAST_REPLAY_PATH: &str = "last_replay.pmgr";
+// Wave table is read from here at startup, so waves can be tuned without rebuilding
+const WAVES_PATH: &str = "assets/waves.txt";
+const HIGH_SCORES_PATH: &str = "high_scores.txt";
+// Level is read from here at startup, unless other one is given by `--level <file>`
+const LEVEL_PATH: &str = "assets/level.txt";
+// Input bindings are read from here at startup
+const BINDINGS_PATH: &str = "assets/bindings.txt";
+// Shaders are read from here and reloaded on change with `--dev-shaders`, embedded ones are used otherwise
+const SHADERS_PATH: &str = "assets/shaders";
+// Seconds between checks of shader files for changes
+const SHADER_POLL_INTERVAL: f64 = 0.25;
+// Red frame around window while shader reload is broken, width in screen space (whole window is 2)
+const SHADER_ERROR_FRAME_WIDTH: f32 = 0.03;
+const SHADER_ERROR_COLOR: [f32; 3] = [1.0, 0.1, 0.1];
+// Draws points right in screen space, -1..1 on both axes
+const SCREEN_VIEW_PROJECTION: [f32; 16] = [
+    1.0, 0.0, 0.0, 0.0,
+    0.0, 1.0, 0.0, 0.0,
+    0.0, 0.0, 1.0, 0.0,
+    0.0, 0.0, 0.0, 1.0,
+];
+// Crosshair is four squares of this size, this far from aim point
+const CROSSHAIR_SQUARE_SIZE: f32 = 0.012;
+const CROSSHAIR_GAP: f32 = 0.025;
+// Gamepad rumble on player hit, hits of this damage or more rumble at full strength
+const RUMBLE_FULL_DAMAGE: f32 = 30.0;
+const RUMBLE_DURATION_MS: u32 = 200;
+
+fn new_seed() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_nanos() as u64)
+        .unwrap_or(0)
+} /* new_seed */
+
+fn unix_time() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+} /* unix_time */
+
+// Save finished run to high score table, if it's good enough
+fn record_high_score(summary: &score::RunSummary, name: &str) {
+    println!("Run over: {summary}");
+
+    let mut table = match score::HighScoreTable::load(HIGH_SCORES_PATH) {
+        Ok(table) => table,
+        // Broken table is moved aside, so it can be inspected, and new one is started
+        Err(error @ score::HighScoreError::Parse {..}) => {
+            let bad_path = format!("{HIGH_SCORES_PATH}.bad");
+            println!("Error loading {HIGH_SCORES_PATH}, it is moved to {bad_path}: {error}");
+            if let Err(error) = std::fs::rename(HIGH_SCORES_PATH, &bad_path) {
+                println!("Error moving {HIGH_SCORES_PATH}, high score is not saved: {error}");
+                return;
+            }
+            score::HighScoreTable::new()
+        }
+        Err(error) => {
+            println!("Error loading {HIGH_SCORES_PATH}, high score is not saved: {error}");
+            return;
+        }
+    };
+
+    let entry = score::HighScore { name: name.to_string(), points: summary.points, date: unix_time(), seed: summary.seed };
+    let Some(rank) = table.insert(entry) else {
+        return;
+    };
+    println!("New high score, rank {}", rank + 1);
+    for (index, entry) in table.entries.iter().enumerate() {
+        println!("{:>2}. {:>8} {} {} (seed {})", index + 1, entry.points, score::format_date(entry.date), entry.name, entry.seed);
+    }
 
-    void main(void) {
-        out_color = vec4(gs_color, 0);
+    if let Err(error) = table.save(HIGH_SCORES_PATH) {
+        println!("Error saving high scores: {error}");
     }
-    "#;
-} /* mod entity_shader */
+} /* record_high_score */
+
+fn load_shader(files: &glsl::ProgramFiles) -> Result<shader::Program, glsl::ShaderError> {
+    shader::compile(&files.load()?.sources())
+} /* load_shader */
+
+// Shader error in single line fitting into window title: what failed and first log line
+fn error_summary(error: &glsl::ShaderError) -> String {
+    error.to_string().lines().take(2).map(str::trim).collect::<Vec<_>>().join(" ")
+} /* error_summary */
+
+// Arena rectangle centers, floor first, then spawn points; uploaded when arena changes, that is on start and restart
+fn upload_arena(buffer: &mut gpu::Buffer, engine: &game::Engine) {
+    let mut vertices: Vec<Vec2> = std::iter::once(&engine.arena.bounds).chain(&engine.arena.obstacles).map(|rectangle| rectangle.center()).collect();
+    vertices.extend_from_slice(engine.spawner.spawn_points());
+    buffer.upload(&vertices, gpu::Usage::Static);
+} /* upload_arena */
+
+fn mouse_button_name(button: sdl2::mouse::MouseButton) -> Option<&'static str> {
+    match button {
+        sdl2::mouse::MouseButton::Left => Some("mouse_left"),
+        sdl2::mouse::MouseButton::Middle => Some("mouse_middle"),
+        sdl2::mouse::MouseButton::Right => Some("mouse_right"),
+        _ => None,
+    }
+} /* mouse_button_name */
 
-pub mod linmath;
-pub mod game;
-pub mod shader;
-pub mod timer;
+fn main() {
+    // `--replay <file>` plays recorded game back instead of reading input,
+    // `--name <name>` sets name high scores are saved with,
+    // `--level <file>` plays given level
+    let args: Vec<String> = std::env::args().collect();
+    let playback = match args.iter().position(|arg| arg == "--replay") {
+        Some(index) => {
+            let path = args.get(index + 1).expect("Replay file path expected after --replay");
+            Some(replay::Replay::load(path).unwrap_or_else(|error| panic!("Error loading replay {path}: {error}")))
+        }
+        None => None,
+    };
 
-type Vec2 = linmath::Vec2<f32>;
+    let player_name = match args.iter().position(|arg| arg == "--name") {
+        Some(index) => args.get(index + 1).expect("Player name expected after --name").clone(),
+        None => std::env::var("USER").or_else(|_| std::env::var("USERNAME")).unwrap_or_else(|_| "player".to_string()),
+    };
 
-fn main() {
     let sdl_instance = sdl2::init().unwrap();
     let sdl_video = sdl_instance.video().unwrap();
+    let sdl_controller = sdl_instance.game_controller().unwrap();
     let mut sdl_event_pump = sdl_instance.event_pump().unwrap();
 
     let gl_attr = sdl_video.gl_attr();
@@ -72,7 +200,7 @@ fn main() {
         .forward_compatible()
         .set();
 
-    let window = sdl_video.window("pixel-mobs-guns", 800, 600)
+    let mut window = sdl_video.window("pixel-mobs-guns", 800, 600)
         .opengl()
         .resizable()
         .build().expect("Error creating SDL2 window");
@@ -84,82 +212,132 @@ fn main() {
     window.gl_make_current(&gl_context).expect("OpenGL context activation error");
     gl::load_with(|name| sdl_video.gl_get_proc_address(name) as *const _);
 
-    let mut timer = timer::Timer::new(&sdl_instance);
-    let mut engine = game::Engine::new();
+    let mut timer = timer::Timer::new(SdlClock { sdl_timer: sdl_instance.timer().unwrap() });
+    let mut fixed_step = game::Engine::fixed_step();
 
-    engine.enemies.push(game::Enemy {
-        position: Vec2::new(0.2, 0.2),
-        health: 0.0,
+    let waves = spawner::WaveTable::load(WAVES_PATH).unwrap_or_else(|error| {
+        println!("Error loading {WAVES_PATH}, built-in waves are used: {error}");
+        spawner::WaveTable::default_waves()
     });
-    engine.enemies.push(game::Enemy {
-        position: Vec2::new(-0.2, 0.2),
-        health: 0.0,
+
+    let bindings = input::InputMap::load(BINDINGS_PATH).unwrap_or_else(|error| {
+        println!("Error loading {BINDINGS_PATH}, built-in bindings are used: {error}");
+        input::InputMap::default_map()
     });
-    engine.enemies.push(game::Enemy {
-        position: Vec2::new(0.2, -0.2),
-        health: 0.0,
+    // Typos are reported, such bindings just never trigger
+    let key_names: Vec<String> = (0..512)
+        .filter_map(sdl2::keyboard::Scancode::from_i32)
+        .map(|scancode| input::normalize_input_name(scancode.name()))
+        .collect();
+    for binding in &bindings.bindings {
+        let pad_input = binding.input.strip_prefix("pad_")
+            .is_some_and(|name| sdl2::controller::Button::from_string(name).is_some() || sdl2::controller::Axis::from_string(name).is_some());
+        let known = matches!(binding.input.as_str(), "mouse" | "mouse_left" | "mouse_middle" | "mouse_right")
+            || pad_input
+            || key_names.contains(&binding.input);
+        if !known {
+            println!("Unknown input '{}' bound to {:?}", binding.input, binding.action);
+        }
+    }
+    let mut input_state = input::InputState::new(bindings);
+    // Connected gamepads, already connected ones are reported as added at startup too
+    let mut controllers = Vec::<sdl2::controller::GameController>::new();
+
+    let level_path = match args.iter().position(|arg| arg == "--level") {
+        Some(index) => args.get(index + 1).expect("Level file path expected after --level").as_str(),
+        None => LEVEL_PATH,
+    };
+    let level = level::Level::load(level_path).unwrap_or_else(|error| {
+        println!("Error loading {level_path}, built-in level is used: {error}");
+        level::Level::default_level()
     });
-    engine.enemies.push(game::Enemy {
-        position: Vec2::new(-0.2, -0.2),
-        health: 0.0,
+
+    // Engine is started from replay, played back game gets waves and level it was recorded with
+    let mut recording = replay::Replay::new(new_seed(), waves.clone(), Some(level.clone()));
+    let mut engine = playback.as_ref().unwrap_or(&recording).start();
+    let mut playback_tick: usize = 0;
+
+    // Dev mode shaders are built from files; if they are broken at start, embedded ones are used until files are fixed
+    let mut shader_files = args.iter().any(|arg| arg == "--dev-shaders").then(|| glsl::ProgramFiles::new(SHADERS_PATH, entity_shader::NAME));
+    let mut shader_poll_time = 0.0;
+    // Error of last reload, shown in title and by frame around window while previous shader is kept
+    let mut shader_error: Option<String> = None;
+    let dev_shader = shader_files.as_ref().and_then(|files| match load_shader(files) {
+        Ok(program) => Some(program),
+        Err(error) => {
+            println!("Error building shaders from {SHADERS_PATH}, built-in ones are used: {error}");
+            shader_error = Some(error_summary(&error));
+            None
+        }
     });
 
-    let shader = shader::compile(Some(entity_shader::FRAG), Some(entity_shader::GEOM), Some(entity_shader::VERT)).unwrap();
-    let point_size_location: i32 = unsafe { gl::GetUniformLocation(shader, std::mem::transmute(b"point_size\0".as_ptr())) };
-    let point_color_location: i32 = unsafe { gl::GetUniformLocation(shader, std::mem::transmute(b"point_color\0".as_ptr())) };
-    let projection_size_location: i32 = unsafe { gl::GetUniformLocation(shader, std::mem::transmute(b"projection_size\0".as_ptr())) };
-
-    let mut player_vertex_array: u32 = 0;
-    let mut player_vertex_buffer: u32 = 0;
-
-    let mut enemy_vertex_array: u32 = 0;
-    let mut enemy_vertex_buffer: u32 = 0;
-
-    let mut bullet_vertex_array: u32 = 0;
-    let mut bullet_vertex_buffer: u32 = 0;
-
-    let mut window_width = 800;
-    let mut window_height = 600;
-
-    let mut mouse_x: f32 = 0.0;
-    let mut mouse_y: f32 = 0.0;
-
-    // OpenGL resource generation
-    unsafe {
-        // Fill player data
-        gl::GenBuffers(1, &mut player_vertex_buffer);
-        gl::BindBuffer(gl::ARRAY_BUFFER, player_vertex_buffer);
-        let data: [f32; 2] = [0.5, 0.5];
-        gl::BufferData(gl::ARRAY_BUFFER, 8, std::mem::transmute(data.as_ptr()), gl::STATIC_DRAW);
-
-        gl::GenVertexArrays(1, &mut player_vertex_array);
-        gl::BindVertexArray(player_vertex_array);
-        gl::EnableVertexAttribArray(0);
-        gl::VertexAttribFormat(0, 2, gl::FLOAT, gl::FALSE, 0);
-        gl::BindVertexBuffer(0, player_vertex_buffer, 0, 8);
-
-        // Generate bullet buffers
-        gl::GenBuffers(1, &mut bullet_vertex_buffer);
-        gl::GenVertexArrays(1, &mut bullet_vertex_array);
-
-        gl::BindVertexArray(bullet_vertex_array);
-        gl::EnableVertexAttribArray(0);
-        gl::VertexAttribFormat(0, 2, gl::FLOAT, gl::FALSE, 0);
-        gl::BindVertexBuffer(0, bullet_vertex_buffer, 0, 8);
-
-        // Generate enemy buffers
-        gl::GenBuffers(1, &mut enemy_vertex_buffer);
-        gl::GenVertexArrays(1, &mut enemy_vertex_array);
-
-        gl::BindVertexArray(enemy_vertex_array);
-        gl::EnableVertexAttribArray(0);
-        gl::VertexAttribFormat(0, 2, gl::FLOAT, gl::FALSE, 0);
-        gl::BindVertexBuffer(0, enemy_vertex_buffer, 0, 8);
-    }
+    let program = match dev_shader {
+        Some(program) => Ok(program),
+        None => shader::compile(&entity_shader::SOURCES),
+    };
+    let program = program.unwrap_or_else(|error| {
+        // Nothing can be drawn without it, so error is shown even if game isn't started from terminal
+        let message = format!("Error building entity shader: {error}");
+        println!("{message}");
+        let _ = sdl2::messagebox::show_simple_message_box(sdl2::messagebox::MessageBoxFlag::ERROR, "pixel-mobs-guns", &message, &window);
+        std::process::exit(1);
+    });
+    let mut shader = entity_shader::EntityShader::new(program);
+
+    // Mouse aim goes through view without shake, so shake doesn't move aim; camera sets it every frame
+    let mut view = view::View::new(800, 600);
+    let mut camera = camera::Camera::new(engine.player.position);
+    camera.snap(engine.player.position, &engine.arena.bounds, view.screen_size);
+    view = camera.steady_view(view.screen_size);
+    // Crosshair replaces system cursor
+    sdl_instance.mouse().show_cursor(false);
+
+    let bullet_vertex_capacity = engine.bullets.capacity() + engine.enemy_bullets.capacity();
+
+    // OpenGL resource generation, everything is deleted when it goes out of scope
+    let player_vertex_buffer = gpu::Buffer::with_capacity::<Vec2>(1, gpu::Usage::Dynamic);
+    let player_vertex_array = gpu::VertexArray::new::<Vec2>(&player_vertex_buffer);
+
+    // Crosshair squares are updated every frame
+    let crosshair_vertex_buffer = gpu::Buffer::with_capacity::<Vec2>(4, gpu::Usage::Dynamic);
+    let crosshair_vertex_array = gpu::VertexArray::new::<Vec2>(&crosshair_vertex_buffer);
+
+    // Shader error frame: top and bottom bars, then left and right ones
+    let mut shader_error_vertex_buffer = gpu::Buffer::new();
+    let frame_edge = 1.0 - SHADER_ERROR_FRAME_WIDTH / 2.0;
+    shader_error_vertex_buffer.upload(
+        &[Vec2::new(0.0, frame_edge), Vec2::new(0.0, -frame_edge), Vec2::new(-frame_edge, 0.0), Vec2::new(frame_edge, 0.0)],
+        gpu::Usage::Static,
+    );
+    let shader_error_vertex_array = gpu::VertexArray::new::<Vec2>(&shader_error_vertex_buffer);
+
+    // Bullet buffer is sized for whole player and enemy bullet pools once
+    let bullet_vertex_buffer = gpu::Buffer::with_capacity::<Vec2>(bullet_vertex_capacity, gpu::Usage::Dynamic);
+    let bullet_vertex_array = gpu::VertexArray::new::<Vec2>(&bullet_vertex_buffer);
+
+    let mut enemy_vertex_buffer = gpu::Buffer::new();
+    let enemy_vertex_array = gpu::VertexArray::new::<Vec2>(&enemy_vertex_buffer);
+
+    let mut pickup_vertex_buffer = gpu::Buffer::new();
+    let pickup_vertex_array = gpu::VertexArray::new::<Vec2>(&pickup_vertex_buffer);
+
+    // Floor and obstacles are drawn as points of their size; arena changes only with engine
+    let mut arena_vertex_buffer = gpu::Buffer::new();
+    let arena_vertex_array = gpu::VertexArray::new::<Vec2>(&arena_vertex_buffer);
+    upload_arena(&mut arena_vertex_buffer, &engine);
+
+    let mut bullet_vertices = Vec::<Vec2>::with_capacity(bullet_vertex_capacity);
+    let mut enemy_vertices = Vec::<Vec2>::new();
+    // First vertex and vertex count for every enemy kind in enemy buffer
+    let mut enemy_ranges = Vec::<(game::EnemyKind, usize, usize)>::with_capacity(enemy::ALL_ENEMY_KINDS.len());
+    let mut pickup_vertices = Vec::<Vec2>::new();
+    let mut pickup_ranges = Vec::<(pickup::PickupKind, usize, usize)>::with_capacity(pickup::ALL_PICKUP_KINDS.len());
+
+    let mut shown_title = String::new();
+    // Run result is saved once, when game is over
+    let mut run_recorded = false;
 
     'main_loop: loop {
-        let mut player_emit_bullet = false;
-
         'event_loop: loop {
             let event = match sdl_event_pump.poll_event() {
                 Some(some_event) => some_event,
@@ -171,31 +349,56 @@ fn main() {
                 sdl2::event::Event::Window {win_event, ..} => {
                     match win_event {
                         sdl2::event::WindowEvent::SizeChanged(new_width, new_height) => {
-                            unsafe {
-                                window_width = new_width;
-                                window_height = new_height;
-                                gl::Viewport(0, 0, new_width, new_height);
-                            }
+                            view.set_screen_size(new_width as u32, new_height as u32);
+                            gpu::viewport(new_width, new_height);
+                        }
+                        // Keys released while unfocused never report it
+                        sdl2::event::WindowEvent::FocusLost => {
+                            input_state.release_all();
                         }
                         _ => {}
                     }
                 }
-                sdl2::event::Event::KeyDown {keycode, ..} => {
-                    match keycode {
-                        Some(keycode_value) => match keycode_value {
-                            sdl2::keyboard::Keycode::Space => player_emit_bullet = true,
-
-                            _ => {}
-                        }
-                        None => {}
+                // Keys are bound by physical position, input state ignores repeats itself
+                sdl2::event::Event::KeyDown {scancode: Some(scancode), ..} => {
+                    input_state.press(scancode.name());
+                }
+                sdl2::event::Event::KeyUp {scancode: Some(scancode), ..} => {
+                    input_state.release(scancode.name());
+                }
+                sdl2::event::Event::MouseButtonDown {mouse_btn, ..} => {
+                    if let Some(name) = mouse_button_name(mouse_btn) {
+                        input_state.press(name);
+                    }
+                }
+                sdl2::event::Event::MouseButtonUp {mouse_btn, ..} => {
+                    if let Some(name) = mouse_button_name(mouse_btn) {
+                        input_state.release(name);
                     }
                 }
                 sdl2::event::Event::MouseMotion {x, y, ..} => {
-                    mouse_x = x as f32 / window_width as f32;
-                    mouse_y = y as f32 / window_height as f32;
-
-                    mouse_x = mouse_x * 2.0 - 1.0;
-                    mouse_y = 1.0 - mouse_y * 2.0;
+                    input_state.point("mouse", Vec2::new(x as f32, y as f32));
+                }
+                sdl2::event::Event::ControllerDeviceAdded {which, ..} => {
+                    match sdl_controller.open(which) {
+                        Ok(controller) => {
+                            println!("Game controller connected: {}", controller.name());
+                            controllers.push(controller);
+                        }
+                        Err(error) => println!("Error opening game controller {which}: {error}"),
+                    }
+                }
+                // Buttons of unplugged gamepad are never released, so everything is
+                sdl2::event::Event::ControllerDeviceRemoved {which, ..} => {
+                    controllers.retain(|controller| controller.instance_id() != which);
+                    // Keys held on keyboard stay held
+                    input_state.release_device("pad_");
+                }
+                sdl2::event::Event::ControllerButtonDown {button, ..} => {
+                    input_state.press(&format!("pad_{}", button.string()));
+                }
+                sdl2::event::Event::ControllerButtonUp {button, ..} => {
+                    input_state.release(&format!("pad_{}", button.string()));
                 }
                 sdl2::event::Event::Quit {..} => {
                     break 'main_loop;
@@ -204,100 +407,264 @@ fn main() {
             }
         }
 
-        timer.update();
+        // Sticks need both axes for deadzone, so they are read after events; most deflected stick of all pads wins
+        if !controllers.is_empty() {
+            let read = |controller: &sdl2::controller::GameController, axis| controller.axis(axis) as f32 / i16::MAX as f32;
+            let sticks = [
+                ("pad_leftx", "pad_lefty", sdl2::controller::Axis::LeftX, sdl2::controller::Axis::LeftY),
+                ("pad_rightx", "pad_righty", sdl2::controller::Axis::RightX, sdl2::controller::Axis::RightY),
+            ];
+            for (x_name, y_name, x_axis, y_axis) in sticks {
+                let stick = controllers.iter()
+                    .map(|controller| input::apply_deadzone(Vec2::new(read(controller, x_axis), read(controller, y_axis)), input::STICK_DEADZONE))
+                    .max_by(|lhs, rhs| lhs.length2().total_cmp(&rhs.length2()))
+                    .unwrap_or(Vec2::new(0.0, 0.0));
+                input_state.set_analog(x_name, stick.x);
+                input_state.set_analog(y_name, stick.y);
+            }
+            for (name, axis) in [("pad_lefttrigger", sdl2::controller::Axis::TriggerLeft), ("pad_righttrigger", sdl2::controller::Axis::TriggerRight)] {
+                let value = controllers.iter().map(|controller| read(controller, axis)).fold(0.0, f32::max);
+                input_state.set_analog(name, value);
+            }
+        }
 
-        let keyboard_state = sdl_event_pump.keyboard_state();
-        let player_move_axis = Vec2::new(
-            keyboard_state.is_scancode_pressed(sdl2::keyboard::Scancode::L) as i32 as f32 - keyboard_state.is_scancode_pressed(sdl2::keyboard::Scancode::J) as i32 as f32,
-            keyboard_state.is_scancode_pressed(sdl2::keyboard::Scancode::I) as i32 as f32 - keyboard_state.is_scancode_pressed(sdl2::keyboard::Scancode::K) as i32 as f32,
-        );
-        engine.player.position += player_move_axis * 0.8 * timer.delta_time as f32;
+        if input_state.take_pressed(input::Action::Pause) {
+            engine.toggle_pause();
+        }
+        // Restart, only live games can be restarted
+        if playback.is_none() && engine.state() == game::GameState::GameOver && input_state.take_pressed(input::Action::Reload) {
+            recording = replay::Replay::new(new_seed(), waves.clone(), Some(level.clone()));
+            run_recorded = false;
+            engine = recording.start();
+            upload_arena(&mut arena_vertex_buffer, &engine);
+            camera.snap(engine.player.position, &engine.arena.bounds, view.screen_size);
+        }
 
-        if player_emit_bullet {
-            let velocity = (Vec2::new(mouse_x, mouse_y) - engine.player.position).normalized();
-            let position = engine.player.position + velocity * 0.01;
+        timer.update();
 
-            engine.bullets.push(game::Bullet{position, velocity});
+        // Paused or finished game runs no ticks, so presses made meanwhile are dropped instead of all reaching first tick after it
+        if matches!(engine.state(), game::GameState::Paused | game::GameState::GameOver) {
+            input_state.end_tick();
         }
 
-        engine.update(timer.delta_time as f32);
-
-        let (projection_w, projection_h) = if window_width > window_height {
-            (window_width as f32 / window_height as f32, 1.0)
-        } else {
-            (1.0, window_height as f32 / window_width as f32)
-        };
+        for _ in 0..fixed_step.accumulate(timer.delta_time) {
+            let input = input_state.player_input(engine.player.position, &view);
 
-        // response
-        unsafe {
-            // Update player
-            gl::BindBuffer(gl::ARRAY_BUFFER, player_vertex_buffer);
-            gl::BufferSubData(gl::ARRAY_BUFFER, 0, 8, std::mem::transmute([engine.player.position.x, engine.player.position.y].as_ptr()));
-
-            // Update enemies
-            let mut enemy_vertices = Vec::<f32>::with_capacity(engine.enemies.len() * 2);
-            for enemy in &engine.enemies {
-                enemy_vertices.push(enemy.position.x);
-                enemy_vertices.push(enemy.position.y);
+            // Ticks of paused or finished game are neither simulated nor recorded
+            if matches!(engine.state(), game::GameState::Paused | game::GameState::GameOver) {
+                break;
             }
 
-            gl::BindBuffer(gl::ARRAY_BUFFER, enemy_vertex_buffer);
-            gl::BufferData(gl::ARRAY_BUFFER, enemy_vertices.len() as isize * 4, std::mem::transmute(enemy_vertices.as_ptr()), gl::STATIC_DRAW);
+            match &playback {
+                Some(playback) => {
+                    if let Some(recorded_input) = playback.inputs.get(playback_tick) {
+                        engine.update(recorded_input);
+                        playback_tick += 1;
+                    }
+                }
+                None => {
+                    recording.record(&input);
+                    engine.update(&input);
+                }
+            }
+            // Hits shake camera and rumble gamepads, harder hits rumble stronger
+            for event in &engine.events {
+                match event {
+                    game::GameEvent::PlayerHit {damage, ..} => {
+                        camera.add_trauma(camera::PLAYER_HIT_TRAUMA);
+                        let strength = (damage / RUMBLE_FULL_DAMAGE).min(1.0);
+                        for controller in &mut controllers {
+                            controller.set_rumble((strength * u16::MAX as f32) as u16, (strength * 0.5 * u16::MAX as f32) as u16, RUMBLE_DURATION_MS).ok();
+                        }
+                    }
+                    game::GameEvent::EnemyKilled {..} => camera.add_trauma(camera::ENEMY_KILLED_TRAUMA),
+                    _ => {}
+                }
+            }
+            // Presses reach exactly one tick
+            input_state.end_tick();
+        }
+        let alpha = fixed_step.alpha();
 
-            // Update bullets
-            let mut bullet_vertices = Vec::<f32>::with_capacity(engine.bullets.len() * 2);
-            for bullet in &engine.bullets {
-                bullet_vertices.push(bullet.position.x);
-                bullet_vertices.push(bullet.position.y);
+        if engine.state() == game::GameState::GameOver && !run_recorded {
+            run_recorded = true;
+            if playback.is_none() {
+                record_high_score(&engine.summary(), &player_name);
             }
+        }
 
-            gl::BindBuffer(gl::ARRAY_BUFFER, bullet_vertex_buffer);
-            gl::BufferData(gl::ARRAY_BUFFER, bullet_vertices.len() as isize * 4, std::mem::transmute(bullet_vertices.as_ptr()), gl::STATIC_DRAW);
+        if let Some(files) = shader_files.as_mut().filter(|_| timer.time - shader_poll_time >= SHADER_POLL_INTERVAL) {
+            shader_poll_time = timer.time;
+            if files.poll_changed() {
+                match load_shader(files) {
+                    Ok(program) => {
+                        println!("Shaders reloaded from {SHADERS_PATH}");
+                        shader = entity_shader::EntityShader::new(program);
+                        shader_error = None;
+                    }
+                    Err(error) => {
+                        println!("Error reloading shaders, previous ones are kept: {error}");
+                        shader_error = Some(error_summary(&error));
+                    }
+                }
+            }
+        }
 
-            // rendering
-            gl::Clear(gl::COLOR_BUFFER_BIT);
-            gl::ClearColor(0.30, 0.47, 0.80, 1.00);
+        let mut title = match engine.state() {
+            game::GameState::Playing => {
+                let weapon = engine.player.weapon();
+                let score = format!("{} x{}", engine.score.points, engine.score.multiplier);
+                if weapon.is_reloading() {
+                    format!("pixel-mobs-guns - {score} - {:?} reloading", weapon.kind)
+                } else {
+                    format!("pixel-mobs-guns - {score} - {:?} {}/{}", weapon.kind, weapon.ammo, weapon.stats().magazine)
+                }
+            }
+            game::GameState::PlayerDead => "pixel-mobs-guns - you died".to_string(),
+            game::GameState::GameOver => "pixel-mobs-guns - game over, press R to restart".to_string(),
+            game::GameState::Paused => "pixel-mobs-guns - paused".to_string(),
+        };
+        if let Some(error) = &shader_error {
+            title = format!("{title} - shader error: {error}");
+        }
+        if title != shown_title {
+            window.set_title(&title).ok();
+            shown_title = title;
+        }
 
-            gl::UseProgram(shader);
-            gl::Uniform2f(projection_size_location, projection_w, projection_h);
+        // Player blinks while invulnerable and disappears when dead
+        let player_visible = engine.player.is_alive() && (engine.player.invulnerable_time * 10.0) as i32 % 2 == 0;
 
-            // Render bullets
-            gl::Uniform2f(point_size_location, 0.02, 0.02);
-            gl::Uniform3f(point_color_location, 1.0, 0.0, 0.0);
-            gl::BindVertexArray(bullet_vertex_array);
-            gl::DrawArrays(gl::POINTS, 0, engine.bullets.len() as i32);
+        camera.update(engine.player.interpolated_position(alpha), &engine.arena.bounds, view.screen_size, timer.delta_time as f32);
+        view = camera.steady_view(view.screen_size);
+        // Only rendered picture shakes, crosshair shakes with world it points into
+        let view_projection = camera.view(view.screen_size).view_projection();
 
-            // Render player
-            gl::Uniform2f(point_size_location, 0.05, 0.05);
-            gl::Uniform3f(point_color_location, 0.0, 1.0, 0.0);
-            gl::BindVertexArray(player_vertex_array);
-            gl::DrawArrays(gl::POINTS, 0, 1);
+        // response
+        // Update player
+        let player_position = engine.player.interpolated_position(alpha);
+        player_vertex_buffer.update(0, &[player_position]);
+
+        // Update crosshair, squares around aim point
+        let aim = input_state.aim(player_position, &view);
+        let crosshair = [
+            aim - Vec2::new(CROSSHAIR_GAP, 0.0),
+            aim + Vec2::new(CROSSHAIR_GAP, 0.0),
+            aim - Vec2::new(0.0, CROSSHAIR_GAP),
+            aim + Vec2::new(0.0, CROSSHAIR_GAP),
+        ];
+        crosshair_vertex_buffer.update(0, &crosshair);
+
+        // Update enemies, grouped by kind
+        enemy_vertices.clear();
+        enemy_ranges.clear();
+        for kind in enemy::ALL_ENEMY_KINDS {
+            let first = enemy_vertices.len();
+            for enemy in engine.enemies.iter().filter(|enemy| enemy.kind == kind) {
+                enemy_vertices.push(enemy.interpolated_position(alpha));
+            }
+            enemy_ranges.push((kind, first, enemy_vertices.len() - first));
+        }
+        enemy_vertex_buffer.upload(&enemy_vertices, gpu::Usage::Dynamic);
+
+        // Update pickups, grouped by kind
+        pickup_vertices.clear();
+        pickup_ranges.clear();
+        for kind in pickup::ALL_PICKUP_KINDS {
+            let first = pickup_vertices.len();
+            for pickup in engine.pickups.iter().filter(|pickup| pickup.kind == kind) {
+                pickup_vertices.push(pickup.position);
+            }
+            pickup_ranges.push((kind, first, pickup_vertices.len() - first));
+        }
+        pickup_vertex_buffer.upload(&pickup_vertices, gpu::Usage::Dynamic);
 
-            // Render enemies
-            gl::Uniform2f(point_size_location, 0.10, 0.10);
-            gl::Uniform3f(point_color_location, 0.55, 0.00, 1.00);
-            gl::BindVertexArray(enemy_vertex_array);
-            gl::DrawArrays(gl::POINTS, 0, engine.enemies.len() as i32);
+        // Update bullets, only alive ones are uploaded
+        bullet_vertices.clear();
+        for bullet in engine.bullets.iter().chain(engine.enemy_bullets.iter()) {
+            bullet_vertices.push(bullet.interpolated_position(alpha));
+        }
+        bullet_vertex_buffer.update(0, &bullet_vertices);
+
+        // rendering
+        gpu::clear([0.10, 0.16, 0.28]);
+
+        // Everything is drawn by entity shader, it is bound once per frame
+        let program = shader.program.bind();
+        program.set(&shader.view_projection, &view_projection);
+
+        // Render arena
+        for (index, rectangle) in std::iter::once(&engine.arena.bounds).chain(&engine.arena.obstacles).enumerate() {
+            program.set(&shader.point_size, &(rectangle.half_size() * 2.0));
+            if index == 0 {
+                program.set(&shader.point_color, &[0.30, 0.47, 0.80]);
+            } else {
+                program.set(&shader.point_color, &[0.12, 0.12, 0.18]);
+            }
+            arena_vertex_array.draw_points(index, 1);
+        }
+        // Spawn points go after rectangles
+        let rectangle_count = 1 + engine.arena.obstacles.len();
+        program.set(&shader.point_size, &Vec2::new(game::PLAYER_SIZE, game::PLAYER_SIZE));
+        program.set(&shader.point_color, &[0.22, 0.30, 0.55]);
+        arena_vertex_array.draw_points(rectangle_count, engine.spawner.spawn_points().len());
+
+        // Render pickups
+        program.set(&shader.point_size, &Vec2::new(pickup::PICKUP_SIZE, pickup::PICKUP_SIZE));
+        for (kind, first, count) in &pickup_ranges {
+            program.set(&shader.point_color, &kind.color());
+            pickup_vertex_array.draw_points(*first, *count);
+        }
 
-            gl::Finish();
+        // Render bullets
+        program.set(&shader.point_size, &Vec2::new(game::BULLET_SIZE, game::BULLET_SIZE));
+        program.set(&shader.point_color, &[1.0, 0.0, 0.0]);
+        bullet_vertex_array.draw_points(0, engine.bullets.len());
+        program.set(&shader.point_color, &[1.0, 0.8, 0.0]);
+        bullet_vertex_array.draw_points(engine.bullets.len(), engine.enemy_bullets.len());
+
+        // Render player
+        if player_visible {
+            program.set(&shader.point_size, &Vec2::new(game::PLAYER_SIZE, game::PLAYER_SIZE));
+            program.set(&shader.point_color, &[0.0, 1.0, 0.0]);
+            player_vertex_array.draw_points(0, 1);
         }
 
-        window.gl_swap_window();
-    }
+        // Render enemies
+        for (kind, first, count) in &enemy_ranges {
+            let stats = kind.stats();
 
-    // Clear all OpenGL-depentent staff
-    unsafe {
-        gl::DeleteVertexArrays(1, &enemy_vertex_array);
-        gl::DeleteBuffers(1, &enemy_vertex_buffer);
+            program.set(&shader.point_size, &Vec2::new(stats.size, stats.size));
+            program.set(&shader.point_color, &stats.color);
+            enemy_vertex_array.draw_points(*first, *count);
+        }
 
-        gl::DeleteVertexArrays(1, &bullet_vertex_array);
-        gl::DeleteBuffers(1, &bullet_vertex_buffer);
+        // Render crosshair over everything, while there is something to aim
+        if engine.state() != game::GameState::GameOver {
+            program.set(&shader.point_size, &Vec2::new(CROSSHAIR_SQUARE_SIZE, CROSSHAIR_SQUARE_SIZE));
+            program.set(&shader.point_color, &[1.0, 1.0, 1.0]);
+            crosshair_vertex_array.draw_points(0, 4);
+        }
 
-        gl::DeleteVertexArrays(1, &player_vertex_array);
-        gl::DeleteBuffers(1, &player_vertex_buffer);
+        // Render shader error frame over everything, until shaders are reloaded fine
+        if shader_error.is_some() {
+            program.set(&shader.view_projection, &SCREEN_VIEW_PROJECTION);
+            program.set(&shader.point_color, &SHADER_ERROR_COLOR);
+            program.set(&shader.point_size, &Vec2::new(2.0, SHADER_ERROR_FRAME_WIDTH));
+            shader_error_vertex_array.draw_points(0, 2);
+            program.set(&shader.point_size, &Vec2::new(SHADER_ERROR_FRAME_WIDTH, 2.0));
+            shader_error_vertex_array.draw_points(2, 2);
+        }
+
+        gpu::finish();
 
-        gl::DeleteProgram(shader);
+        window.gl_swap_window();
     }
 
-    println!("Hello, world!");
+    if playback.is_none() {
+        match recording.save(LAST_REPLAY_PATH) {
+            Ok(()) => println!("Replay saved to {LAST_REPLAY_PATH}"),
+            Err(error) => println!("Error saving replay: {error}"),
+        }
+    }
 } /* main */
@@ -0,0 +1,189 @@
+/* Navigation grid over arena and flow field towards single target, enemies follow it around obstacles */
+
+use crate::arena::Arena;
+use crate::collision::Aabb;
+use crate::game::Vec2;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+// Move costs, diagonal one is ~sqrt(2) times orthogonal
+const STRAIGHT_COST: u32 = 10;
+const DIAGONAL_COST: u32 = 14;
+const UNREACHABLE: u32 = u32::MAX;
+const NO_CELL: u32 = u32::MAX;
+
+// Path is followed towards cell this many steps ahead, so movement isn't bound to 8 directions
+pub const FLOW_LOOKAHEAD: usize = 3;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct NavGrid {
+    // Corner of cell (0, 0)
+    pub origin: Vec2,
+    pub cell_size: f32,
+    pub width: usize,
+    pub height: usize,
+    // Row by row, row 0 at origin
+    pub blocked: Vec<bool>,
+} /* NavGrid */
+
+// Path costs from every cell to target and next cell on the way there
+#[derive(Clone, Debug)]
+pub struct FlowField {
+    target: Option<usize>,
+    costs: Vec<u32>,
+    next: Vec<u32>,
+    // Dijkstra queue, kept between computations
+    open: BinaryHeap<Reverse<(u32, u32)>>,
+} /* FlowField */
+
+impl NavGrid {
+    // All cells free
+    pub fn new(bounds: &Aabb, cell_size: f32) -> NavGrid {
+        let size = bounds.max - bounds.min;
+        let width = (size.x / cell_size).ceil().max(1.0) as usize;
+        let height = (size.y / cell_size).ceil().max(1.0) as usize;
+
+        NavGrid { origin: bounds.min, cell_size, width, height, blocked: vec![false; width * height] }
+    } /* new */
+
+    // Cells closer than clearance to walls or obstacles are blocked
+    pub fn from_arena(arena: &Arena, cell_size: f32, clearance: f32) -> NavGrid {
+        let mut grid = NavGrid::new(&arena.bounds, cell_size);
+        let inner = arena.bounds.expanded(-clearance);
+        let obstacles: Vec<Aabb> = arena.obstacles.iter().map(|obstacle| obstacle.expanded(clearance)).collect();
+
+        for index in 0..grid.blocked.len() {
+            let center = grid.cell_center(index);
+            grid.blocked[index] = !inner.contains(center) || obstacles.iter().any(|obstacle| obstacle.contains(center));
+        }
+        grid
+    } /* from_arena */
+
+    pub fn cell_count(&self) -> usize {
+        self.width * self.height
+    } /* cell_count */
+
+    pub fn cell_index(&self, position: Vec2) -> Option<usize> {
+        let cell = (position - self.origin) / self.cell_size;
+        if cell.x < 0.0 || cell.y < 0.0 || cell.x >= self.width as f32 || cell.y >= self.height as f32 {
+            return None;
+        }
+        Some(cell.y as usize * self.width + cell.x as usize)
+    } /* cell_index */
+
+    pub fn cell_center(&self, index: usize) -> Vec2 {
+        let column = (index % self.width) as f32;
+        let row = (index / self.width) as f32;
+        self.origin + Vec2::new(column + 0.5, row + 0.5) * self.cell_size
+    } /* cell_center */
+
+    pub fn is_blocked(&self, index: usize) -> bool {
+        self.blocked[index]
+    } /* is_blocked */
+
+    // Calls visit(neighbour, cost) for every neighbour, diagonal steps to free cells can't cut blocked corners
+    fn for_each_neighbour(&self, index: usize, mut visit: impl FnMut(usize, u32)) {
+        let column = (index % self.width) as isize;
+        let row = (index / self.width) as isize;
+        let inside = |column: isize, row: isize| column >= 0 && row >= 0 && (column as usize) < self.width && (row as usize) < self.height;
+        let free = |column: isize, row: isize| inside(column, row) && !self.blocked[row as usize * self.width + column as usize];
+
+        for (dx, dy) in [(-1, -1), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)] {
+            if !inside(column + dx, row + dy) {
+                continue;
+            }
+            let diagonal = dx != 0 && dy != 0;
+            if diagonal && free(column + dx, row + dy) && (!free(column + dx, row) || !free(column, row + dy)) {
+                continue;
+            }
+            let neighbour = (row + dy) as usize * self.width + (column + dx) as usize;
+            visit(neighbour, if diagonal { DIAGONAL_COST } else { STRAIGHT_COST });
+        }
+    } /* for_each_neighbour */
+} /* impl NavGrid */
+
+impl FlowField {
+    pub fn new() -> FlowField {
+        FlowField { target: None, costs: Vec::new(), next: Vec::new(), open: BinaryHeap::new() }
+    } /* new */
+
+    pub fn target(&self) -> Option<usize> {
+        self.target
+    } /* target */
+
+    // Recompute paths to cell containing target; nothing is done if it is the same cell as last time
+    pub fn update(&mut self, grid: &NavGrid, target: Vec2) {
+        let target = grid.cell_index(target);
+        if target == self.target && self.costs.len() == grid.cell_count() {
+            return;
+        }
+        self.target = target;
+        self.compute(grid);
+    } /* update */
+
+    fn compute(&mut self, grid: &NavGrid) {
+        self.costs.clear();
+        self.costs.resize(grid.cell_count(), UNREACHABLE);
+        self.next.clear();
+        self.next.resize(grid.cell_count(), NO_CELL);
+        self.open.clear();
+
+        let Some(target) = self.target else {
+            return;
+        };
+
+        // Paths only go through free cells, but blocked cells next to them get way out too,
+        // as bodies brush along walls closer than clearance. Target may be blocked as well.
+        self.costs[target] = 0;
+        self.open.push(Reverse((0, target as u32)));
+
+        while let Some(Reverse((cost, index))) = self.open.pop() {
+            let index = index as usize;
+            if cost > self.costs[index] {
+                continue;
+            }
+
+            grid.for_each_neighbour(index, |neighbour, step_cost| {
+                let neighbour_cost = cost + step_cost;
+                if neighbour_cost < self.costs[neighbour] {
+                    self.costs[neighbour] = neighbour_cost;
+                    self.next[neighbour] = index as u32;
+                    if !grid.is_blocked(neighbour) {
+                        self.open.push(Reverse((neighbour_cost, neighbour as u32)));
+                    }
+                }
+            });
+        }
+    } /* compute */
+
+    // Path cost from cell to target, None if target can't be reached
+    pub fn cost(&self, index: usize) -> Option<u32> {
+        self.costs.get(index).copied().filter(|cost| *cost != UNREACHABLE)
+    } /* cost */
+
+    // Next cell on path from given one
+    pub fn next(&self, index: usize) -> Option<usize> {
+        self.next.get(index).copied().filter(|next| *next != NO_CELL).map(|next| next as usize)
+    } /* next */
+
+    // Direction to move from position, None near target (or where it can't be reached) - head straight there
+    pub fn direction(&self, grid: &NavGrid, position: Vec2) -> Option<Vec2> {
+        let mut cell = grid.cell_index(position)?;
+
+        for _ in 0..FLOW_LOOKAHEAD {
+            cell = self.next(cell)?;
+        }
+
+        let offset = grid.cell_center(cell) - position;
+        if offset.length2() == 0.0 {
+            return None;
+        }
+        Some(offset.normalized())
+    } /* direction */
+} /* impl FlowField */
+
+impl Default for FlowField {
+    fn default() -> FlowField {
+        FlowField::new()
+    }
+} /* impl Default for FlowField */
@@ -0,0 +1,125 @@
+/* Pickups dropped by killed enemies and their drop tables */
+
+use crate::collision::Circle;
+use crate::game::{EnemyKind, Vec2};
+use crate::rng::Rng;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PickupKind {
+    // Restores part of player health
+    Health,
+    // Refills magazines of all weapons
+    Ammo,
+    // Timed weapon upgrade: more damage and extra pierce
+    WeaponBoost,
+    // Timed movement speed increase
+    SpeedBoost,
+} /* PickupKind */
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Pickup {
+    pub kind: PickupKind,
+    pub position: Vec2,
+    // Seconds left before pickup disappears
+    pub lifetime: f32,
+} /* Pickup */
+
+// Chance of single pickup kind to drop on kill
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PickupDrop {
+    pub kind: PickupKind,
+    pub chance: f32,
+} /* PickupDrop */
+
+pub const ALL_PICKUP_KINDS: [PickupKind; 4] = [
+    PickupKind::Health,
+    PickupKind::Ammo,
+    PickupKind::WeaponBoost,
+    PickupKind::SpeedBoost,
+];
+
+pub const PICKUP_SIZE: f32 = 0.04;
+pub const PICKUP_RADIUS: f32 = PICKUP_SIZE / 2.0;
+pub const PICKUP_LIFETIME: f32 = 10.0;
+
+pub const HEALTH_PICKUP_AMOUNT: f32 = 25.0;
+pub const WEAPON_BOOST_DURATION: f32 = 8.0;
+pub const WEAPON_BOOST_DAMAGE_SCALE: f32 = 2.0;
+pub const WEAPON_BOOST_EXTRA_PIERCE: u32 = 1;
+pub const SPEED_BOOST_DURATION: f32 = 6.0;
+pub const SPEED_BOOST_SCALE: f32 = 1.5;
+
+// What kill of given enemy kind may drop, chances sum up to at most 1
+pub const fn drop_table(kind: EnemyKind) -> &'static [PickupDrop] {
+    match kind {
+        EnemyKind::Chaser => &[
+            PickupDrop { kind: PickupKind::Health, chance: 0.04 },
+            PickupDrop { kind: PickupKind::Ammo, chance: 0.06 },
+        ],
+        EnemyKind::Brute => &[
+            PickupDrop { kind: PickupKind::Health, chance: 0.25 },
+            PickupDrop { kind: PickupKind::WeaponBoost, chance: 0.15 },
+        ],
+        EnemyKind::Orbiter => &[
+            PickupDrop { kind: PickupKind::SpeedBoost, chance: 0.08 },
+            PickupDrop { kind: PickupKind::Ammo, chance: 0.05 },
+        ],
+        EnemyKind::Dasher => &[
+            PickupDrop { kind: PickupKind::SpeedBoost, chance: 0.15 },
+            PickupDrop { kind: PickupKind::Health, chance: 0.05 },
+        ],
+        EnemyKind::Splitter => &[
+            PickupDrop { kind: PickupKind::WeaponBoost, chance: 0.10 },
+            PickupDrop { kind: PickupKind::Ammo, chance: 0.10 },
+        ],
+        EnemyKind::Splitling => &[],
+        EnemyKind::Shooter => &[
+            PickupDrop { kind: PickupKind::Ammo, chance: 0.20 },
+            PickupDrop { kind: PickupKind::WeaponBoost, chance: 0.10 },
+        ],
+    }
+} /* drop_table */
+
+impl PickupKind {
+    pub fn from_name(name: &str) -> Option<PickupKind> {
+        match name {
+            "health" => Some(PickupKind::Health),
+            "ammo" => Some(PickupKind::Ammo),
+            "weapon_boost" => Some(PickupKind::WeaponBoost),
+            "speed_boost" => Some(PickupKind::SpeedBoost),
+            _ => None,
+        }
+    } /* from_name */
+
+    pub const fn color(&self) -> [f32; 3] {
+        match self {
+            PickupKind::Health => [1.0, 1.0, 1.0],
+            PickupKind::Ammo => [0.6, 0.6, 0.6],
+            PickupKind::WeaponBoost => [1.0, 0.2, 0.2],
+            PickupKind::SpeedBoost => [0.2, 1.0, 1.0],
+        }
+    } /* color */
+} /* impl PickupKind */
+
+impl Pickup {
+    pub fn new(kind: PickupKind, position: Vec2) -> Pickup {
+        Pickup { kind, position, lifetime: PICKUP_LIFETIME }
+    } /* new */
+
+    pub fn circle(&self) -> Circle {
+        Circle::new(self.position, PICKUP_RADIUS)
+    } /* circle */
+
+    // Roll drop table of killed enemy, at most one pickup is dropped
+    pub fn roll(enemy: EnemyKind, position: Vec2, rng: &mut Rng) -> Option<Pickup> {
+        let mut roll = rng.next_f32();
+
+        for drop in drop_table(enemy) {
+            if roll < drop.chance {
+                return Some(Pickup::new(drop.kind, position));
+            }
+            roll -= drop.chance;
+        }
+        None
+    } /* roll */
+} /* impl Pickup */
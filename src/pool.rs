@@ -0,0 +1,72 @@
+/* Fixed capacity entity pool: storage is allocated once and reused by dead entities */
+
+pub struct Pool<T> {
+    items: Vec<T>,
+    capacity: usize,
+} /* Pool */
+
+impl<T> Pool<T> {
+    pub fn new(capacity: usize) -> Pool<T> {
+        Pool { items: Vec::with_capacity(capacity), capacity }
+    } /* new */
+
+    // Add item, returns false (and drops item) if pool is full
+    pub fn spawn(&mut self, item: T) -> bool {
+        if self.items.len() >= self.capacity {
+            return false;
+        }
+        self.items.push(item);
+        true
+    } /* spawn */
+
+    // Keep only alive items, freed slots are reused by next spawns
+    pub fn retain(&mut self, alive: impl FnMut(&mut T) -> bool) {
+        self.items.retain_mut(alive);
+    } /* retain */
+
+    pub fn clear(&mut self) {
+        self.items.clear();
+    } /* clear */
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    } /* len */
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    } /* is_empty */
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    } /* capacity */
+
+    pub fn as_slice(&self) -> &[T] {
+        &self.items
+    } /* as_slice */
+
+    pub fn iter(&self) -> std::slice::Iter<'_, T> {
+        self.items.iter()
+    } /* iter */
+
+    pub fn iter_mut(&mut self) -> std::slice::IterMut<'_, T> {
+        self.items.iter_mut()
+    } /* iter_mut */
+} /* impl Pool */
+
+impl<'a, T> IntoIterator for &'a Pool<T> {
+    type Item = &'a T;
+    type IntoIter = std::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> std::slice::Iter<'a, T> {
+        self.items.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut Pool<T> {
+    type Item = &'a mut T;
+    type IntoIter = std::slice::IterMut<'a, T>;
+
+    fn into_iter(self) -> std::slice::IterMut<'a, T> {
+        self.items.iter_mut()
+    }
+}
@@ -0,0 +1,217 @@
+/* Replay recording and playback: seed + per-tick player inputs */
+
+use crate::game::{Engine, PlayerInput, Vec2, TICK_RATE};
+use crate::level::{Level, LevelError};
+use crate::spawner::{WaveTable, WaveTableError};
+use std::io::{Read, Write};
+
+// File layout (little endian):
+//   magic "PMGR", version: u16, seed: u64, wave table length: u32, wave table: UTF-8 text,
+//   level length: u32, level: UTF-8 text, run count: u32,
+//   runs: { length: u32, move x/y: f32, aim x/y: f32, flags: u8, weapon slot: u8 }
+// Wave table and level are stored in WaveTable::parse and Level::parse formats, so replay plays the same
+// waves on the same map whatever the current files are. Empty level text means open arena without level.
+// Consecutive equal inputs are stored as one run. Weapon slot is 0 for no switch, slot + 1 otherwise.
+const MAGIC: &[u8; 4] = b"PMGR";
+const VERSION: u16 = 4;
+
+// Longest replay loaded, lengths in file are not trusted to allocate for
+pub const MAX_REPLAY_TICKS: usize = 4 * 60 * 60 * TICK_RATE as usize;
+
+const FLAG_FIRE: u8 = 1 << 0;
+const FLAG_RELOAD: u8 = 1 << 1;
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(std::io::Error),
+    BadMagic,
+    UnsupportedVersion(u16),
+    TooLong,
+    Waves(WaveTableError),
+    Level(LevelError),
+} /* ReplayError */
+
+impl std::fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReplayError::Io(error) => write!(f, "replay I/O error: {error}"),
+            ReplayError::BadMagic => write!(f, "not a replay file"),
+            ReplayError::UnsupportedVersion(version) => write!(f, "unsupported replay version {version}"),
+            ReplayError::TooLong => write!(f, "replay is longer than {MAX_REPLAY_TICKS} ticks"),
+            ReplayError::Waves(error) => write!(f, "replay wave table: {error}"),
+            ReplayError::Level(error) => write!(f, "replay level: {error}"),
+        }
+    }
+} /* impl std::fmt::Display for ReplayError */
+
+impl std::error::Error for ReplayError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ReplayError::Io(error) => Some(error),
+            ReplayError::Waves(error) => Some(error),
+            ReplayError::Level(error) => Some(error),
+            _ => None,
+        }
+    }
+} /* impl std::error::Error for ReplayError */
+
+impl From<std::io::Error> for ReplayError {
+    fn from(error: std::io::Error) -> ReplayError {
+        ReplayError::Io(error)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Replay {
+    pub seed: u64,
+    pub waves: WaveTable,
+    pub level: Option<Level>,
+    pub inputs: Vec<PlayerInput>,
+} /* Replay */
+
+impl Replay {
+    pub fn new(seed: u64, waves: WaveTable, level: Option<Level>) -> Replay {
+        Replay { seed, waves, level, inputs: Vec::new() }
+    } /* new */
+
+    // Save input of next tick
+    pub fn record(&mut self, input: &PlayerInput) {
+        self.inputs.push(*input);
+    } /* record */
+
+    // Fresh engine replay starts from, new games are started by it too
+    pub fn start(&self) -> Engine {
+        let mut engine = Engine::new_game_with_waves(self.seed, self.waves.clone());
+        if let Some(level) = &self.level {
+            engine.load_level(level.clone());
+        }
+        engine
+    } /* start */
+
+    // Run whole replay, engine state equals to one recorded game ended with
+    pub fn play(&self) -> Engine {
+        let mut engine = self.start();
+
+        for input in &self.inputs {
+            engine.update(input);
+        }
+
+        engine
+    } /* play */
+
+    pub fn write_to(&self, writer: &mut impl Write) -> Result<(), ReplayError> {
+        let mut runs = Vec::<(u32, PlayerInput)>::new();
+
+        for input in &self.inputs {
+            match runs.last_mut() {
+                Some((length, last)) if last == input && *length < u32::MAX => *length += 1,
+                _ => runs.push((1, *input)),
+            }
+        }
+
+        writer.write_all(MAGIC)?;
+        writer.write_all(&VERSION.to_le_bytes())?;
+        writer.write_all(&self.seed.to_le_bytes())?;
+        write_text(writer, &self.waves.to_source())?;
+        write_text(writer, &self.level.as_ref().map_or(String::new(), Level::to_source))?;
+        writer.write_all(&(runs.len() as u32).to_le_bytes())?;
+
+        for (length, input) in &runs {
+            writer.write_all(&length.to_le_bytes())?;
+            for value in [input.move_axis.x, input.move_axis.y, input.aim.x, input.aim.y] {
+                writer.write_all(&value.to_le_bytes())?;
+            }
+            let mut flags = 0;
+            if input.fire {
+                flags |= FLAG_FIRE;
+            }
+            if input.reload {
+                flags |= FLAG_RELOAD;
+            }
+            let slot = input.switch_weapon.map_or(0, |slot| slot.min(u8::MAX as usize - 1) as u8 + 1);
+            writer.write_all(&[flags, slot])?;
+        }
+
+        Ok(())
+    } /* write_to */
+
+    pub fn read_from(reader: &mut impl Read) -> Result<Replay, ReplayError> {
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(ReplayError::BadMagic);
+        }
+
+        let version = u16::from_le_bytes(read_array(reader)?);
+        if version != VERSION {
+            return Err(ReplayError::UnsupportedVersion(version));
+        }
+
+        let seed = u64::from_le_bytes(read_array(reader)?);
+        let waves = WaveTable::parse(&read_text(reader)?).map_err(ReplayError::Waves)?;
+        let level = match read_text(reader)? {
+            text if text.is_empty() => None,
+            text => Some(Level::parse(&text).map_err(ReplayError::Level)?),
+        };
+        let mut replay = Replay::new(seed, waves, level);
+        let run_count = u32::from_le_bytes(read_array(reader)?);
+
+        for _ in 0..run_count {
+            let length = u32::from_le_bytes(read_array(reader)?);
+            let mut values = [0.0f32; 4];
+            for value in &mut values {
+                *value = f32::from_le_bytes(read_array(reader)?);
+            }
+            let [flags, slot] = read_array::<2>(reader)?;
+
+            let input = PlayerInput {
+                move_axis: Vec2::new(values[0], values[1]),
+                aim: Vec2::new(values[2], values[3]),
+                fire: flags & FLAG_FIRE != 0,
+                reload: flags & FLAG_RELOAD != 0,
+                switch_weapon: slot.checked_sub(1).map(usize::from),
+            };
+            if length as usize > MAX_REPLAY_TICKS - replay.inputs.len() {
+                return Err(ReplayError::TooLong);
+            }
+            replay.inputs.extend(std::iter::repeat_n(input, length as usize));
+        }
+
+        Ok(replay)
+    } /* read_from */
+
+    pub fn save(&self, path: impl AsRef<std::path::Path>) -> Result<(), ReplayError> {
+        let mut writer = std::io::BufWriter::new(std::fs::File::create(path)?);
+        self.write_to(&mut writer)?;
+        writer.flush()?;
+        Ok(())
+    } /* save */
+
+    pub fn load(path: impl AsRef<std::path::Path>) -> Result<Replay, ReplayError> {
+        Replay::read_from(&mut std::io::BufReader::new(std::fs::File::open(path)?))
+    } /* load */
+} /* impl Replay */
+
+fn read_array<const N: usize>(reader: &mut impl Read) -> Result<[u8; N], ReplayError> {
+    let mut bytes = [0u8; N];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes)
+} /* read_array */
+
+fn write_text(writer: &mut impl Write, text: &str) -> Result<(), ReplayError> {
+    let length = u32::try_from(text.len()).map_err(|_| ReplayError::TooLong)?;
+    writer.write_all(&length.to_le_bytes())?;
+    writer.write_all(text.as_bytes())?;
+    Ok(())
+} /* write_text */
+
+// Length is read from file, text is read through take() so it can't allocate past file end
+fn read_text(reader: &mut impl Read) -> Result<String, ReplayError> {
+    let length = u32::from_le_bytes(read_array(reader)?) as usize;
+    let mut text = String::new();
+    reader.take(length as u64).read_to_string(&mut text)?;
+    if text.len() != length {
+        return Err(ReplayError::Io(std::io::ErrorKind::UnexpectedEof.into()));
+    }
+    Ok(text)
+} /* read_text */
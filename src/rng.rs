@@ -0,0 +1,40 @@
+// Small seeded pseudo-random generator (xorshift64*), same sequence on every platform
+
+#[derive(Copy, Clone, Debug)]
+pub struct Rng {
+    state: u64,
+} /* Rng */
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        // splitmix64 step, so that close seeds give unrelated sequences and state is never zero
+        let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+
+        Rng { state: if z == 0 { 0x2545_F491_4F6C_DD1D } else { z } }
+    } /* new */
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    } /* next_u64 */
+
+    // Uniform value in [0, 1)
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    } /* next_f32 */
+
+    // Uniform value in [min, max)
+    pub fn range_f32(&mut self, min: f32, max: f32) -> f32 {
+        min + (max - min) * self.next_f32()
+    } /* range_f32 */
+
+    // Uniform value in [0, bound), bound must be non-zero
+    pub fn below(&mut self, bound: u32) -> u32 {
+        (((self.next_u64() >> 32) * bound as u64) >> 32) as u32
+    } /* below */
+} /* impl Rng */
@@ -0,0 +1,286 @@
+/* Run score with combo multiplier, end of run summary and persistent high-score table */
+
+use crate::game::EnemyKind;
+use std::io::Write;
+
+// Kills needed to raise multiplier by one
+pub const COMBO_KILLS_PER_LEVEL: u32 = 5;
+pub const MAX_MULTIPLIER: u32 = 8;
+// Multiplier drops by one level after this many seconds without kill
+pub const COMBO_WINDOW: f32 = 2.0;
+pub const MAX_HIGH_SCORES: usize = 10;
+
+const HIGH_SCORE_HEADER: &str = "# pixel-mobs-gun high scores v1";
+
+pub const fn kill_points(kind: EnemyKind) -> u64 {
+    match kind {
+        EnemyKind::Chaser => 10,
+        EnemyKind::Brute => 50,
+        EnemyKind::Orbiter => 20,
+        EnemyKind::Dasher => 30,
+        EnemyKind::Splitter => 30,
+        EnemyKind::Splitling => 5,
+        EnemyKind::Shooter => 40,
+    }
+} /* kill_points */
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Score {
+    pub points: u64,
+    pub kills: u32,
+    pub multiplier: u32,
+    // Kills since multiplier last changed
+    pub combo_kills: u32,
+    // Seconds until multiplier drops
+    pub combo_time: f32,
+    pub bullets_fired: u32,
+    // Bullets that hit at least one enemy
+    pub bullets_hit: u32,
+    pub time_survived: f32,
+} /* Score */
+
+// Statistics shown when run is over
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct RunSummary {
+    pub points: u64,
+    pub kills: u32,
+    // Fraction of bullets hit, 0 if nothing was fired
+    pub accuracy: f32,
+    pub time_survived: f32,
+    pub wave: u32,
+    pub seed: u64,
+} /* RunSummary */
+
+impl Score {
+    pub fn new() -> Score {
+        Score {
+            points: 0,
+            kills: 0,
+            multiplier: 1,
+            combo_kills: 0,
+            combo_time: 0.0,
+            bullets_fired: 0,
+            bullets_hit: 0,
+            time_survived: 0.0,
+        }
+    } /* new */
+
+    pub fn update(&mut self, delta_time: f32) {
+        self.time_survived += delta_time;
+
+        if self.multiplier == 1 {
+            return;
+        }
+        self.combo_time -= delta_time;
+        if self.combo_time <= 0.0 {
+            self.multiplier -= 1;
+            self.combo_kills = 0;
+            self.combo_time = COMBO_WINDOW;
+        }
+    } /* update */
+
+    pub fn record_kill(&mut self, kind: EnemyKind) {
+        self.points += kill_points(kind) * self.multiplier as u64;
+        self.kills += 1;
+
+        self.combo_time = COMBO_WINDOW;
+        self.combo_kills += 1;
+        if self.combo_kills >= COMBO_KILLS_PER_LEVEL && self.multiplier < MAX_MULTIPLIER {
+            self.multiplier += 1;
+            self.combo_kills = 0;
+        }
+    } /* record_kill */
+
+    pub fn accuracy(&self) -> f32 {
+        if self.bullets_fired == 0 {
+            return 0.0;
+        }
+        self.bullets_hit as f32 / self.bullets_fired as f32
+    } /* accuracy */
+
+    pub fn summary(&self, wave: u32, seed: u64) -> RunSummary {
+        RunSummary {
+            points: self.points,
+            kills: self.kills,
+            accuracy: self.accuracy(),
+            time_survived: self.time_survived,
+            wave,
+            seed,
+        }
+    } /* summary */
+} /* impl Score */
+
+impl Default for Score {
+    fn default() -> Score {
+        Score::new()
+    }
+}
+
+impl std::fmt::Display for RunSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "score {}, kills {}, accuracy {:.1}%, survived {:.1}s, wave {}, seed {}",
+            self.points, self.kills, self.accuracy * 100.0, self.time_survived, self.wave, self.seed
+        )
+    }
+} /* impl std::fmt::Display for RunSummary */
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct HighScore {
+    pub name: String,
+    pub points: u64,
+    // Unix time, seconds
+    pub date: u64,
+    pub seed: u64,
+} /* HighScore */
+
+// Best runs, sorted from best to worst
+#[derive(Clone, Debug, PartialEq)]
+pub struct HighScoreTable {
+    pub entries: Vec<HighScore>,
+} /* HighScoreTable */
+
+#[derive(Debug)]
+pub enum HighScoreError {
+    Io(std::io::Error),
+    Parse { line: usize, message: String },
+} /* HighScoreError */
+
+impl std::fmt::Display for HighScoreError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HighScoreError::Io(error) => write!(f, "high score I/O error: {error}"),
+            HighScoreError::Parse { line, message } => write!(f, "high score line {line}: {message}"),
+        }
+    }
+} /* impl std::fmt::Display for HighScoreError */
+
+impl std::error::Error for HighScoreError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            HighScoreError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+} /* impl std::error::Error for HighScoreError */
+
+impl From<std::io::Error> for HighScoreError {
+    fn from(error: std::io::Error) -> HighScoreError {
+        HighScoreError::Io(error)
+    }
+}
+
+impl HighScoreTable {
+    pub fn new() -> HighScoreTable {
+        HighScoreTable { entries: Vec::new() }
+    } /* new */
+
+    // Would run with these points get into table
+    pub fn qualifies(&self, points: u64) -> bool {
+        self.entries.len() < MAX_HIGH_SCORES || self.entries.last().is_some_and(|last| points > last.points)
+    } /* qualifies */
+
+    // Add entry, returns its rank (0 is best) or None if it didn't make it into table.
+    // Equal scores keep older entries first.
+    pub fn insert(&mut self, entry: HighScore) -> Option<usize> {
+        let rank = self.entries.iter().position(|other| entry.points > other.points).unwrap_or(self.entries.len());
+        if rank >= MAX_HIGH_SCORES {
+            return None;
+        }
+        self.entries.insert(rank, entry);
+        self.entries.truncate(MAX_HIGH_SCORES);
+        Some(rank)
+    } /* insert */
+
+    // Text format: header line, then `points seed date name` per line
+    pub fn parse(source: &str) -> Result<HighScoreTable, HighScoreError> {
+        let mut table = HighScoreTable::new();
+        let mut lines = source.lines().enumerate();
+
+        if lines.next().map(|(_, line)| line.trim()) != Some(HIGH_SCORE_HEADER) {
+            return Err(HighScoreError::Parse { line: 1, message: "missing high score header".to_string() });
+        }
+
+        for (line_index, line) in lines {
+            let error = |message: String| HighScoreError::Parse { line: line_index + 1, message };
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let mut fields = line.splitn(4, ' ');
+            let (Some(points), Some(seed), Some(date), Some(name)) = (fields.next(), fields.next(), fields.next(), fields.next()) else {
+                return Err(error("expected 4 fields (points seed date name)".to_string()));
+            };
+
+            table.entries.push(HighScore {
+                name: name.to_string(),
+                points: points.parse().map_err(|_| error(format!("invalid points '{points}'")))?,
+                date: date.parse().map_err(|_| error(format!("invalid date '{date}'")))?,
+                seed: seed.parse().map_err(|_| error(format!("invalid seed '{seed}'")))?,
+            });
+        }
+
+        table.entries.sort_by_key(|entry| std::cmp::Reverse(entry.points));
+        table.entries.truncate(MAX_HIGH_SCORES);
+        Ok(table)
+    } /* parse */
+
+    pub fn write_to(&self, writer: &mut impl Write) -> std::io::Result<()> {
+        writeln!(writer, "{HIGH_SCORE_HEADER}")?;
+        for entry in &self.entries {
+            // Name is the rest of line, so it must stay on one
+            let name: String = entry.name.chars().map(|c| if c.is_control() { ' ' } else { c }).collect();
+            writeln!(writer, "{} {} {} {}", entry.points, entry.seed, entry.date, name)?;
+        }
+        Ok(())
+    } /* write_to */
+
+    // Missing file is an empty table
+    pub fn load(path: impl AsRef<std::path::Path>) -> Result<HighScoreTable, HighScoreError> {
+        match std::fs::read_to_string(path) {
+            Ok(source) => HighScoreTable::parse(&source),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(HighScoreTable::new()),
+            Err(error) => Err(error.into()),
+        }
+    } /* load */
+
+    // Table is written next to target and renamed over it, so crash mid-write can't corrupt existing file
+    pub fn save(&self, path: impl AsRef<std::path::Path>) -> Result<(), HighScoreError> {
+        let path = path.as_ref();
+        let mut temporary_path = path.as_os_str().to_owned();
+        temporary_path.push(".tmp");
+
+        let file = std::fs::File::create(&temporary_path)?;
+        let mut writer = std::io::BufWriter::new(file);
+        self.write_to(&mut writer)?;
+        let file = writer.into_inner().map_err(|error| error.into_error())?;
+        file.sync_all()?;
+        drop(file);
+
+        std::fs::rename(&temporary_path, path)?;
+        Ok(())
+    } /* save */
+} /* impl HighScoreTable */
+
+impl Default for HighScoreTable {
+    fn default() -> HighScoreTable {
+        HighScoreTable::new()
+    }
+}
+
+// Unix time to `YYYY-MM-DD` (UTC)
+pub fn format_date(unix_seconds: u64) -> String {
+    // Days to civil date, see http://howardhinnant.github.io/date_algorithms.html
+    let days = (unix_seconds / 86400) as i64 + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!("{year:04}-{month:02}-{day:02}")
+} /* format_date */
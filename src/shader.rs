@@ -1,12 +1,133 @@
-fn compile_shader_module(module_type: gl::types::GLenum, source: &str) -> Option<u32> {
+// Shader programs and their typed uniforms; programs are deleted on drop, GL context must outlive them
+
+use pixel_mobs_gun_rs::glsl::{self, ProgramSources, ShaderError, ShaderStage};
+use pixel_mobs_gun_rs::linmath;
+use std::marker::PhantomData;
+
+pub struct Program {
+    id: u32,
+} /* Program */
+
+// Program made current by Program::bind, uniforms of the program are set through it
+pub struct BoundProgram<'a> {
+    program: &'a Program,
+} /* BoundProgram */
+
+// Uniform location of program, T is GLSL type it is set as; program id is kept to check it is set on right program
+pub struct Uniform<T> {
+    program: u32,
+    location: i32,
+    value_type: PhantomData<T>,
+} /* Uniform */
+
+impl<T> Uniform<T> {
+    // Stands for uniform program doesn't have, setting it does nothing (GL ignores location -1)
+    pub fn inactive(program: &Program) -> Uniform<T> {
+        Uniform { program: program.id, location: -1, value_type: PhantomData }
+    } /* inactive */
+} /* impl Uniform */
+
+// Values uniforms can be set to
+pub trait UniformValue {
+    fn set_uniform(location: i32, value: &Self);
+} /* UniformValue */
+
+// vec2
+impl UniformValue for linmath::Vec2<f32> {
+    fn set_uniform(location: i32, value: &Self) {
+        unsafe {
+            gl::Uniform2f(location, value.x, value.y);
+        }
+    }
+}
+
+// vec3
+impl UniformValue for [f32; 3] {
+    fn set_uniform(location: i32, value: &Self) {
+        unsafe {
+            gl::Uniform3f(location, value[0], value[1], value[2]);
+        }
+    }
+}
+
+// mat4, column major
+impl UniformValue for [f32; 16] {
+    fn set_uniform(location: i32, value: &Self) {
+        unsafe {
+            gl::UniformMatrix4fv(location, 1, gl::FALSE, value.as_ptr());
+        }
+    }
+}
+
+impl Program {
+    // Program is used by draws until other one is bound
+    pub fn bind(&self) -> BoundProgram<'_> {
+        unsafe {
+            gl::UseProgram(self.id);
+        }
+        BoundProgram { program: self }
+    } /* bind */
+
+    // Uniform by name, None if program has no such active uniform (e.g. it is optimized out)
+    pub fn uniform<T: UniformValue>(&self, name: &std::ffi::CStr) -> Option<Uniform<T>> {
+        let location = unsafe { gl::GetUniformLocation(self.id, name.as_ptr()) };
+        if location < 0 {
+            return None;
+        }
+        Some(Uniform { program: self.id, location, value_type: PhantomData })
+    } /* uniform */
+} /* impl Program */
+
+impl BoundProgram<'_> {
+    // Set uniform of bound program, uniform must be looked up from it
+    pub fn set<T: UniformValue>(&self, uniform: &Uniform<T>, value: &T) {
+        debug_assert_eq!(uniform.program, self.program.id, "uniform is set on program it doesn't belong to");
+        T::set_uniform(uniform.location, value);
+    } /* set */
+} /* impl BoundProgram */
+
+impl Drop for Program {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteProgram(self.id);
+        }
+    }
+} /* impl Drop for Program */
+
+fn gl_stage(stage: ShaderStage) -> gl::types::GLenum {
+    match stage {
+        ShaderStage::Vertex => gl::VERTEX_SHADER,
+        ShaderStage::Geometry => gl::GEOMETRY_SHADER,
+        ShaderStage::Fragment => gl::FRAGMENT_SHADER,
+    }
+} /* gl_stage */
+
+// Whole info log of shader or program, its length is queried first so nothing is cut off
+unsafe fn info_log(
+    object: u32,
+    get_parameter: unsafe fn(u32, gl::types::GLenum, *mut i32),
+    get_log: unsafe fn(u32, i32, *mut i32, *mut gl::types::GLchar),
+) -> String {
+    let mut log_length: i32 = 0;
+    get_parameter(object, gl::INFO_LOG_LENGTH, &mut log_length);
+
+    let mut buffer = vec![0u8; log_length.max(1) as usize];
+    let mut written: i32 = 0;
+    get_log(object, buffer.len() as i32, &mut written, buffer.as_mut_ptr().cast());
+    buffer.truncate(written.max(0) as usize);
+
+    String::from_utf8_lossy(&buffer).into_owned()
+} /* info_log */
+
+fn compile_shader_module(stage: ShaderStage, source: &str) -> Result<u32, ShaderError> {
     unsafe {
-        let shader = gl::CreateShader(module_type);
-        
+        let shader = gl::CreateShader(gl_stage(stage));
+
         if shader == 0 {
-            return None;
+            return Err(ShaderError::Compile { stage, log: "shader object creation failed".to_string() });
         }
 
-        let ptr: *const i8 = std::mem::transmute(source.as_ptr());
+        let ptr: *const i8 = source.as_ptr().cast();
         let len = source.len() as i32;
 
         gl::ShaderSource(shader, 1, &ptr, &len); 
@@ -16,45 +137,48 @@ fn compile_shader_module(module_type: gl::types::GLenum, source: &str) -> Option
 
         gl::GetShaderiv(shader, gl::COMPILE_STATUS, &mut compilation_status);
         if compilation_status != (gl::TRUE as i32) {
-            let mut buffer = std::vec::Vec::<u8>::new();
-            buffer.resize(512, 0);
-            let mut buffer_length: i32 = 0;
-            gl::GetShaderInfoLog(shader, 512, &mut buffer_length, std::mem::transmute(buffer.as_mut_ptr()));
-
-            let log = String::from_utf8_lossy(buffer.as_slice());
-            println!("Shader compilation error: {log}");
-
+            let log = info_log(shader, gl::GetShaderiv, gl::GetShaderInfoLog);
             gl::DeleteShader(shader);
-            return None;
+            return Err(ShaderError::Compile { stage, log: glsl::annotate_log(&log, source) });
         }
 
-        return Some(shader);
+        Ok(shader)
     }
 } /* compile_shader_module */
 
-pub fn compile(vert_source: Option<&str>, geom_source: Option<&str>, frag_source: Option<&str>) -> Option<u32> {
+// Geometry shader is optional, empty vertex or fragment shader is missing one
+pub fn compile(sources: &ProgramSources) -> Result<Program, ShaderError> {
+    for (stage, source) in sources.stages() {
+        if source.trim().is_empty() {
+            return Err(ShaderError::MissingStage(stage));
+        }
+    }
+
     unsafe {
-        let mut build_descriptions: [(u32, Option<&str>, Option<u32>); 3] = [
-            (gl::VERTEX_SHADER,   vert_source, None),
-            (gl::FRAGMENT_SHADER, frag_source, None),
-            (gl::GEOMETRY_SHADER, geom_source, None),
-        ];
-
-        for descr in &mut build_descriptions {
-            match descr.1 {
-                Some(source) => descr.2 = compile_shader_module(descr.0, source),
-                None => continue,
+        let mut shaders = Vec::<u32>::with_capacity(3);
+        let mut result = Ok(());
+
+        for (stage, source) in sources.stages() {
+            match compile_shader_module(stage, source) {
+                Ok(shader) => shaders.push(shader),
+                Err(error) => {
+                    result = Err(error);
+                    break;
+                }
             }
         }
 
-        let mut program = gl::CreateProgram();
+        let mut program = 0;
+        if result.is_ok() {
+            program = gl::CreateProgram();
+            if program == 0 {
+                result = Err(ShaderError::Link { log: "program object creation failed".to_string() });
+            }
+        }
 
         if program != 0 {
-            for descr in &build_descriptions {
-                match descr.2 {
-                    Some(shader) => gl::AttachShader(program, shader),
-                    None => {},
-                }
+            for shader in &shaders {
+                gl::AttachShader(program, *shader);
             }
 
             gl::LinkProgram(program);
@@ -62,25 +186,15 @@ pub fn compile(vert_source: Option<&str>, geom_source: Option<&str>, frag_source
             let mut status: i32 = 0;
             gl::GetProgramiv(program, gl::LINK_STATUS, &mut status);
             if status != (gl::TRUE as i32) {
-                let mut buffer = std::vec::Vec::<u8>::new();
-                buffer.resize(512, 0);
-                let mut buffer_length: i32 = 0;
-                gl::GetProgramInfoLog(program, 512, &mut buffer_length, std::mem::transmute(buffer.as_mut_ptr()));
-
-                let log = String::from_utf8_lossy(buffer.as_slice());
-                println!("Program linking error: {log}");
+                result = Err(ShaderError::Link { log: info_log(program, gl::GetProgramiv, gl::GetProgramInfoLog) });
                 gl::DeleteProgram(program);
-                program = 0;
             }
         }
 
-        for descr in &mut build_descriptions {
-            match descr.2 {
-                Some(shader) => gl::DeleteShader(shader),
-                None => {}
-            }
+        for shader in shaders {
+            gl::DeleteShader(shader);
         }
 
-        if program == 0 { None } else { Some(program) }
+        result.map(|()| Program { id: program })
     }
-} /* compile_shader_program */
+} /* compile */
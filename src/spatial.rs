@@ -0,0 +1,88 @@
+/* Uniform grid spatial hash, broad phase for entity pair queries */
+
+use crate::game::Vec2;
+use std::collections::HashMap;
+use std::hash::{BuildHasherDefault, Hasher};
+
+// Multiplicative hasher for cell coordinates, much cheaper than default SipHash
+#[derive(Default)]
+struct CellHasher {
+    hash: u64,
+} /* CellHasher */
+
+impl Hasher for CellHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.write_u64(*byte as u64);
+        }
+    }
+
+    fn write_i32(&mut self, value: i32) {
+        self.write_u64(value as u32 as u64);
+    }
+
+    fn write_u64(&mut self, value: u64) {
+        self.hash = (self.hash.rotate_left(5) ^ value).wrapping_mul(0x51_7C_C1_B7_27_22_0A_95);
+    }
+
+    fn finish(&self) -> u64 {
+        self.hash
+    }
+} /* impl Hasher for CellHasher */
+
+pub struct SpatialHash {
+    cell_size: f32,
+    cells: HashMap<(i32, i32), Vec<usize>, BuildHasherDefault<CellHasher>>,
+} /* SpatialHash */
+
+impl SpatialHash {
+    // Cell size should be not less than largest interaction distance,
+    // then any query touches at most 3x3 cells
+    pub fn new(cell_size: f32) -> SpatialHash {
+        SpatialHash { cell_size, cells: HashMap::default() }
+    } /* new */
+
+    pub fn cell_size(&self) -> f32 {
+        self.cell_size
+    } /* cell_size */
+
+    fn cell(&self, position: Vec2) -> (i32, i32) {
+        ((position.x / self.cell_size).floor() as i32, (position.y / self.cell_size).floor() as i32)
+    } /* cell */
+
+    // Remove all entries. Cell storage is kept for reuse, cells that stayed empty are dropped.
+    pub fn clear(&mut self) {
+        self.cells.retain(|_, indices| !indices.is_empty());
+        for indices in self.cells.values_mut() {
+            indices.clear();
+        }
+    } /* clear */
+
+    pub fn insert(&mut self, index: usize, position: Vec2) {
+        let cell = self.cell(position);
+        self.cells.entry(cell).or_default().push(index);
+    } /* insert */
+
+    // Rebuild from scratch for set of positions, entry index is position index
+    pub fn rebuild(&mut self, positions: impl Iterator<Item = Vec2>) {
+        self.clear();
+        for (index, position) in positions.enumerate() {
+            self.insert(index, position);
+        }
+    } /* rebuild */
+
+    // Put indices of all entries, which may be closer than radius to position, to result.
+    // Indices come in deterministic order (by cell, then by insertion).
+    pub fn query(&self, position: Vec2, radius: f32, result: &mut Vec<usize>) {
+        let (min_x, min_y) = self.cell(position - radius);
+        let (max_x, max_y) = self.cell(position + radius);
+
+        for y in min_y..=max_y {
+            for x in min_x..=max_x {
+                if let Some(indices) = self.cells.get(&(x, y)) {
+                    result.extend_from_slice(indices);
+                }
+            }
+        }
+    } /* query */
+} /* impl SpatialHash */
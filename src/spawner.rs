@@ -0,0 +1,306 @@
+/* Wave based enemy spawner, configured by wave table (see assets/waves.txt) */
+
+use crate::collision::Aabb;
+use crate::game::{Enemy, EnemyKind, GameEvent, Vec2};
+use crate::rng::Rng;
+
+// Wave table compiled into the game, used when no other one is given
+pub const DEFAULT_WAVES: &str = include_str!("../assets/waves.txt");
+
+// Delay before first wave and between waves, in seconds
+pub const WAVE_DELAY: f32 = 2.0;
+// Enemy count growth for every pass over wave table
+pub const ESCALATION_PER_CYCLE: f32 = 0.5;
+// Most enemies single group line may spawn, before escalation
+pub const MAX_GROUP_COUNT: u32 = 1000;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SpawnPattern {
+    Edges,
+    Side,
+    Corners,
+    Ring,
+} /* SpawnPattern */
+
+// Group of enemies of single kind spawned during wave
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SpawnGroup {
+    pub kind: EnemyKind,
+    pub count: u32,
+    // Seconds between two spawns of the group
+    pub interval: f32,
+    pub pattern: SpawnPattern,
+} /* SpawnGroup */
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Wave {
+    pub number: u32,
+    pub groups: Vec<SpawnGroup>,
+} /* Wave */
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct WaveTable {
+    pub waves: Vec<Wave>,
+} /* WaveTable */
+
+#[derive(Debug)]
+pub enum WaveTableError {
+    Io(std::io::Error),
+    Parse { line: usize, message: String },
+} /* WaveTableError */
+
+impl std::fmt::Display for WaveTableError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WaveTableError::Io(error) => write!(f, "wave table I/O error: {error}"),
+            WaveTableError::Parse { line, message } => write!(f, "wave table line {line}: {message}"),
+        }
+    }
+} /* impl std::fmt::Display for WaveTableError */
+
+impl std::error::Error for WaveTableError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            WaveTableError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+} /* impl std::error::Error for WaveTableError */
+
+impl From<std::io::Error> for WaveTableError {
+    fn from(error: std::io::Error) -> WaveTableError {
+        WaveTableError::Io(error)
+    }
+}
+
+impl SpawnPattern {
+    pub fn from_name(name: &str) -> Option<SpawnPattern> {
+        match name {
+            "edges" => Some(SpawnPattern::Edges),
+            "side" => Some(SpawnPattern::Side),
+            "corners" => Some(SpawnPattern::Corners),
+            "ring" => Some(SpawnPattern::Ring),
+            _ => None,
+        }
+    } /* from_name */
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SpawnPattern::Edges => "edges",
+            SpawnPattern::Side => "side",
+            SpawnPattern::Corners => "corners",
+            SpawnPattern::Ring => "ring",
+        }
+    } /* name */
+} /* impl SpawnPattern */
+
+impl WaveTable {
+    pub fn empty() -> WaveTable {
+        WaveTable { waves: Vec::new() }
+    } /* empty */
+
+    // Parse table: `wave kind count interval pattern` per line, `#` starts comment;
+    // groups of more than one enemy need positive interval, so spawning takes time
+    pub fn parse(source: &str) -> Result<WaveTable, WaveTableError> {
+        let mut table = WaveTable::empty();
+
+        for (line_index, line) in source.lines().enumerate() {
+            let error = |message: String| WaveTableError::Parse { line: line_index + 1, message };
+
+            let content = line.split('#').next().unwrap_or("");
+            let fields: Vec<&str> = content.split_whitespace().collect();
+            if fields.is_empty() {
+                continue;
+            }
+            let [number, kind, count, interval, pattern] = fields[..] else {
+                return Err(error(format!("expected 5 fields (wave kind count interval pattern), got {}", fields.len())));
+            };
+
+            let number: u32 = number.parse().map_err(|_| error(format!("invalid wave number '{number}'")))?;
+            let group = SpawnGroup {
+                kind: EnemyKind::from_name(kind).ok_or_else(|| error(format!("unknown enemy kind '{kind}'")))?,
+                count: count.parse().ok().filter(|count: &u32| *count <= MAX_GROUP_COUNT)
+                    .ok_or_else(|| error(format!("invalid count '{count}', at most {MAX_GROUP_COUNT} allowed")))?,
+                interval: interval.parse().ok().filter(|interval: &f32| interval.is_finite() && *interval >= 0.0)
+                    .ok_or_else(|| error(format!("invalid spawn interval '{interval}'")))?,
+                pattern: SpawnPattern::from_name(pattern).ok_or_else(|| error(format!("unknown spawn pattern '{pattern}'")))?,
+            };
+            if group.count > 1 && group.interval == 0.0 {
+                return Err(error(format!("group of {} enemies needs positive spawn interval", group.count)));
+            }
+
+            match table.waves.iter_mut().find(|wave| wave.number == number) {
+                Some(wave) => wave.groups.push(group),
+                None => table.waves.push(Wave { number, groups: vec![group] }),
+            }
+        }
+
+        table.waves.sort_by_key(|wave| wave.number);
+        Ok(table)
+    } /* parse */
+
+    pub fn load(path: impl AsRef<std::path::Path>) -> Result<WaveTable, WaveTableError> {
+        WaveTable::parse(&std::fs::read_to_string(path)?)
+    } /* load */
+
+    // Table in parse() format, it parses back to equal table
+    pub fn to_source(&self) -> String {
+        let mut source = String::new();
+
+        for wave in &self.waves {
+            for group in &wave.groups {
+                source += &format!("{} {} {} {} {}\n", wave.number, group.kind.name(), group.count, group.interval, group.pattern.name());
+            }
+        }
+
+        source
+    } /* to_source */
+
+    pub fn default_waves() -> WaveTable {
+        WaveTable::parse(DEFAULT_WAVES).expect("Built-in wave table must be valid")
+    } /* default_waves */
+} /* impl WaveTable */
+
+// Runtime state of single group in current wave
+#[derive(Copy, Clone, Debug)]
+struct GroupState {
+    group: SpawnGroup,
+    spawned: u32,
+    cooldown: f32,
+    // Side for Side pattern
+    side: u32,
+} /* GroupState */
+
+pub struct Spawner {
+    table: WaveTable,
+    // Number of waves started, 0 before first one
+    wave: u32,
+    groups: Vec<GroupState>,
+    // Time until next wave starts, counted only when current one is over
+    delay: f32,
+    // Level defined spawn points, used instead of arena edges if there are any
+    spawn_points: Vec<Vec2>,
+} /* Spawner */
+
+impl Spawner {
+    pub fn new(table: WaveTable) -> Spawner {
+        Spawner { table, wave: 0, groups: Vec::new(), delay: WAVE_DELAY, spawn_points: Vec::new() }
+    } /* new */
+
+    // Number of current wave, starting from 1; 0 before first wave
+    pub fn wave(&self) -> u32 {
+        self.wave
+    } /* wave */
+
+    pub fn table(&self) -> &WaveTable {
+        &self.table
+    } /* table */
+
+    pub fn spawn_points(&self) -> &[Vec2] {
+        &self.spawn_points
+    } /* spawn_points */
+
+    pub fn set_spawn_points(&mut self, spawn_points: Vec<Vec2>) {
+        self.spawn_points = spawn_points;
+    } /* set_spawn_points */
+
+    fn is_spawning(&self) -> bool {
+        self.groups.iter().any(|group| group.spawned < group.group.count)
+    } /* is_spawning */
+
+    fn start_wave(&mut self, rng: &mut Rng) {
+        let length = self.table.waves.len() as u32;
+        let cycle = self.wave / length;
+        let scale = 1.0 + ESCALATION_PER_CYCLE * cycle as f32;
+
+        self.groups.clear();
+        for group in &self.table.waves[(self.wave % length) as usize].groups {
+            let mut group = *group;
+            group.count = (group.count as f32 * scale).round() as u32;
+
+            self.groups.push(GroupState { group, spawned: 0, cooldown: 0.0, side: rng.below(4) });
+        }
+        self.wave += 1;
+    } /* start_wave */
+
+    pub fn update(&mut self, delta_time: f32, bounds: &Aabb, rng: &mut Rng, enemies: &mut Vec<Enemy>, events: &mut Vec<GameEvent>) {
+        if self.table.waves.is_empty() {
+            return;
+        }
+
+        // Wave is over when everything is spawned and killed
+        if !self.is_spawning() && enemies.is_empty() {
+            self.delay -= delta_time;
+            if self.delay > 0.0 {
+                return;
+            }
+            self.delay = WAVE_DELAY;
+            self.start_wave(rng);
+            events.push(GameEvent::WaveStarted { wave: self.wave });
+        }
+
+        for state in &mut self.groups {
+            state.cooldown -= delta_time;
+
+            // Spawn area is arena edge, moved inside by enemy radius
+            let area = bounds.expanded(-state.group.kind.radius());
+
+            while state.spawned < state.group.count && state.cooldown <= 0.0 {
+                let position = match self.spawn_points.len() {
+                    0 => spawn_position(state, &area, rng),
+                    // Ring pattern goes over points in turn, others pick random ones
+                    count if state.group.pattern == SpawnPattern::Ring => self.spawn_points[state.spawned as usize % count],
+                    count => self.spawn_points[rng.below(count as u32) as usize],
+                };
+                let mut enemy = Enemy::new(state.group.kind, position);
+
+                enemy.orbit_direction = if rng.below(2) == 0 { 1.0 } else { -1.0 };
+                enemies.push(enemy);
+                state.spawned += 1;
+                state.cooldown += state.group.interval;
+            }
+        }
+    } /* update */
+} /* impl Spawner */
+
+// Point on rectangle perimeter, t in [0, 1) goes counter-clockwise from min corner
+fn perimeter_point(area: &Aabb, t: f32) -> Vec2 {
+    let size = area.max - area.min;
+    let mut distance = t.fract() * 2.0 * (size.x + size.y);
+
+    if distance < size.x {
+        return Vec2::new(area.min.x + distance, area.min.y);
+    }
+    distance -= size.x;
+    if distance < size.y {
+        return Vec2::new(area.max.x, area.min.y + distance);
+    }
+    distance -= size.y;
+    if distance < size.x {
+        return Vec2::new(area.max.x - distance, area.max.y);
+    }
+    distance -= size.x;
+    Vec2::new(area.min.x, area.max.y - distance)
+} /* perimeter_point */
+
+fn spawn_position(state: &GroupState, area: &Aabb, rng: &mut Rng) -> Vec2 {
+    match state.group.pattern {
+        SpawnPattern::Edges => perimeter_point(area, rng.next_f32()),
+        SpawnPattern::Side => {
+            let t = rng.next_f32();
+            match state.side {
+                0 => Vec2::new(area.min.x + (area.max.x - area.min.x) * t, area.min.y),
+                1 => Vec2::new(area.max.x, area.min.y + (area.max.y - area.min.y) * t),
+                2 => Vec2::new(area.min.x + (area.max.x - area.min.x) * t, area.max.y),
+                _ => Vec2::new(area.min.x, area.min.y + (area.max.y - area.min.y) * t),
+            }
+        }
+        SpawnPattern::Corners => match state.spawned % 4 {
+            0 => area.min,
+            1 => Vec2::new(area.max.x, area.min.y),
+            2 => area.max,
+            _ => Vec2::new(area.min.x, area.max.y),
+        },
+        SpawnPattern::Ring => perimeter_point(area, state.spawned as f32 / state.group.count.max(1) as f32),
+    }
+} /* spawn_position */
@@ -0,0 +1,102 @@
+/* Steering behaviours for enemy crowds: separation, alignment, cohesion and obstacle avoidance */
+
+use crate::collision::Aabb;
+use crate::game::{Enemy, Vec2};
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SteeringConfig {
+    // Enemies closer than sum of their radii plus this margin push each other apart
+    pub separation_margin: f32,
+    pub separation_weight: f32,
+    // Alignment and cohesion consider neighbours within this distance
+    pub neighbour_radius: f32,
+    pub alignment_weight: f32,
+    pub cohesion_weight: f32,
+    // Enemies start turning away from obstacles closer than this (from enemy edge)
+    pub avoidance_distance: f32,
+    pub avoidance_weight: f32,
+} /* SteeringConfig */
+
+impl Default for SteeringConfig {
+    // Plain separation and avoidance, mobs spread but don't flock
+    fn default() -> SteeringConfig {
+        SteeringConfig {
+            separation_margin: 0.02,
+            separation_weight: 2.0,
+            neighbour_radius: 0.3,
+            alignment_weight: 0.0,
+            cohesion_weight: 0.0,
+            avoidance_distance: 0.1,
+            avoidance_weight: 3.0,
+        }
+    }
+} /* impl Default for SteeringConfig */
+
+impl SteeringConfig {
+    // Distance neighbour query must cover
+    pub fn query_radius(&self, max_radius: f32) -> f32 {
+        self.neighbour_radius.max(max_radius * 2.0 + self.separation_margin)
+    } /* query_radius */
+
+    // Velocity correction for enemies[index], neighbours are candidate indices from broad phase
+    pub fn steer(&self, index: usize, enemies: &[Enemy], neighbours: &[usize], obstacles: &[Aabb]) -> Vec2 {
+        let enemy = &enemies[index];
+        let speed = enemy.kind.stats().speed;
+        let radius = enemy.kind.radius();
+
+        let mut separation = Vec2::new(0.0, 0.0);
+        let mut velocity_sum = Vec2::new(0.0, 0.0);
+        let mut position_sum = Vec2::new(0.0, 0.0);
+        let mut flock_size = 0;
+
+        for &other_index in neighbours {
+            if other_index == index {
+                continue;
+            }
+            let other = &enemies[other_index];
+            let offset = enemy.position - other.position;
+            let distance = offset.length();
+
+            let separation_distance = radius + other.kind.radius() + self.separation_margin;
+            if distance < separation_distance {
+                // Coincident enemies are pushed apart by index, so result stays deterministic
+                let direction = if distance > 0.0001 {
+                    offset / distance
+                } else if index < other_index {
+                    Vec2::new(1.0, 0.0)
+                } else {
+                    Vec2::new(-1.0, 0.0)
+                };
+                separation += direction * (1.0 - distance / separation_distance);
+            }
+
+            if distance < self.neighbour_radius {
+                velocity_sum += other.velocity;
+                position_sum += other.position;
+                flock_size += 1;
+            }
+        }
+
+        let mut correction = separation * self.separation_weight * speed;
+
+        if flock_size > 0 {
+            let average_velocity = velocity_sum / flock_size as f32;
+            let center = position_sum / flock_size as f32;
+
+            correction += (average_velocity - enemy.velocity) * self.alignment_weight;
+            correction += (center - enemy.position) * self.cohesion_weight * speed;
+        }
+
+        for obstacle in obstacles {
+            let offset = enemy.position - obstacle.closest_point(enemy.position);
+            let distance = offset.length();
+            let reach = radius + self.avoidance_distance;
+
+            if distance < reach && distance > 0.0001 {
+                correction += offset / distance * (1.0 - distance / reach) * self.avoidance_weight * speed;
+            }
+        }
+
+        correction
+    } /* steer */
+} /* impl SteeringConfig */
@@ -1,5 +1,65 @@
-pub struct Timer {
-    sdl_timer: sdl2::TimerSubsystem,
+// Time source abstraction, so timer can be driven by SDL, std or by hand (in tests)
+pub trait Clock {
+    fn ticks(&self) -> u64;
+    fn ticks_per_second(&self) -> u64;
+} /* Clock */
+
+// Clock based on std::time::Instant
+pub struct StdClock {
+    start: std::time::Instant,
+} /* StdClock */
+
+impl StdClock {
+    pub fn new() -> StdClock {
+        StdClock { start: std::time::Instant::now() }
+    } /* new */
+} /* impl StdClock */
+
+impl Default for StdClock {
+    fn default() -> StdClock {
+        StdClock::new()
+    }
+}
+
+impl Clock for StdClock {
+    fn ticks(&self) -> u64 {
+        self.start.elapsed().as_nanos() as u64
+    }
+
+    fn ticks_per_second(&self) -> u64 {
+        1_000_000_000
+    }
+} /* impl Clock for StdClock */
+
+// Clock that only moves when it is told to
+pub struct ManualClock {
+    ticks: std::cell::Cell<u64>,
+    ticks_per_second: u64,
+} /* ManualClock */
+
+impl ManualClock {
+    pub fn new(ticks_per_second: u64) -> ManualClock {
+        ManualClock { ticks: std::cell::Cell::new(0), ticks_per_second }
+    } /* new */
+
+    pub fn advance(&self, seconds: f64) {
+        let delta = (seconds * self.ticks_per_second as f64) as u64;
+        self.ticks.set(self.ticks.get() + delta);
+    } /* advance */
+} /* impl ManualClock */
+
+impl Clock for ManualClock {
+    fn ticks(&self) -> u64 {
+        self.ticks.get()
+    }
+
+    fn ticks_per_second(&self) -> u64 {
+        self.ticks_per_second
+    }
+} /* impl Clock for ManualClock */
+
+pub struct Timer<C: Clock> {
+    clock: C,
 
     ticks_per_second: u64,
     initial_ticks: u64,
@@ -13,15 +73,13 @@ pub struct Timer {
     pub delta_time: f64,
 }
 
-impl Timer {
-    pub fn new(sdl_instance: &sdl2::Sdl) -> Timer {
-        let sdl_timer = sdl_instance.timer().unwrap();
-
-        let ticks_per_second = sdl_timer.performance_frequency();
-        let initial_ticks = sdl_timer.performance_counter();
+impl<C: Clock> Timer<C> {
+    pub fn new(clock: C) -> Timer<C> {
+        let ticks_per_second = clock.ticks_per_second();
+        let initial_ticks = clock.ticks();
 
         Timer {
-            sdl_timer,
+            clock,
             ticks_per_second,
             initial_ticks,
             last_ticks: initial_ticks,
@@ -31,10 +89,14 @@ impl Timer {
             time: 0.0,
             delta_time: 0.001,
         }
-    }
+    } /* new */
+
+    pub fn clock(&self) -> &C {
+        &self.clock
+    } /* clock */
 
     pub fn update(&mut self) {
-        let ticks = self.sdl_timer.performance_counter();
+        let ticks = self.clock.ticks();
 
         self.time = (ticks - self.initial_ticks) as f64 / (self.ticks_per_second as f64);
         self.delta_time = (ticks - self.last_ticks) as f64 / self.ticks_per_second as f64;
@@ -46,5 +108,42 @@ impl Timer {
             self.fps_counter = 0;
             self.fps_last_ticks = self.last_ticks;
         }
-    }
+    } /* update */
 }
+
+// Fixed timestep accumulator: converts variable frame time into whole simulation ticks
+pub struct FixedStep {
+    step: f64,
+    max_steps: u32,
+    accumulator: f64,
+} /* FixedStep */
+
+impl FixedStep {
+    // max_steps caps ticks per frame, so long stalls drop time instead of spiralling
+    pub fn new(tick_rate: f64, max_steps: u32) -> FixedStep {
+        FixedStep { step: 1.0 / tick_rate, max_steps, accumulator: 0.0 }
+    } /* new */
+
+    pub fn step(&self) -> f64 {
+        self.step
+    } /* step */
+
+    // Add frame time, get number of ticks to simulate this frame
+    pub fn accumulate(&mut self, delta_time: f64) -> u32 {
+        self.accumulator += delta_time.max(0.0);
+
+        let mut steps = (self.accumulator / self.step) as u32;
+        if steps > self.max_steps {
+            steps = self.max_steps;
+            self.accumulator = self.step * steps as f64;
+        }
+        self.accumulator -= self.step * steps as f64;
+
+        steps
+    } /* accumulate */
+
+    // Fraction of tick passed since last simulated one, for render interpolation
+    pub fn alpha(&self) -> f32 {
+        (self.accumulator / self.step).clamp(0.0, 1.0) as f32
+    } /* alpha */
+} /* impl FixedStep */
@@ -0,0 +1,59 @@
+/* View: part of world shown in window, converts between screen and world positions for input and rendering */
+
+use crate::game::Vec2;
+
+// Distance from view center to nearest window edge in world units at zoom 1, longer window side shows more
+pub const VIEW_EXTENT: f32 = 1.0;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct View {
+    // World position shown in window center
+    pub center: Vec2,
+    // Window size in pixels
+    pub screen_size: Vec2,
+    // Magnification, 2 shows half as much of world
+    pub zoom: f32,
+} /* View */
+
+impl View {
+    pub fn new(screen_width: u32, screen_height: u32) -> View {
+        View { center: Vec2::new(0.0, 0.0), screen_size: Vec2::new(screen_width.max(1) as f32, screen_height.max(1) as f32), zoom: 1.0 }
+    } /* new */
+
+    pub fn set_screen_size(&mut self, screen_width: u32, screen_height: u32) {
+        self.screen_size = Vec2::new(screen_width.max(1) as f32, screen_height.max(1) as f32);
+    } /* set_screen_size */
+
+    // Visible world half width and height, aspect ratio is kept
+    pub fn half_size(&self) -> Vec2 {
+        let extent = VIEW_EXTENT / self.zoom;
+        if self.screen_size.x > self.screen_size.y {
+            Vec2::new(self.screen_size.x / self.screen_size.y, 1.0) * extent
+        } else {
+            Vec2::new(1.0, self.screen_size.y / self.screen_size.x) * extent
+        }
+    } /* half_size */
+
+    // Screen position in pixels, y down from top left corner, to world position
+    pub fn screen_to_world(&self, screen: Vec2) -> Vec2 {
+        let normalized = Vec2::new(screen.x / self.screen_size.x * 2.0 - 1.0, 1.0 - screen.y / self.screen_size.y * 2.0);
+        self.center + normalized * self.half_size()
+    } /* screen_to_world */
+
+    pub fn world_to_screen(&self, world: Vec2) -> Vec2 {
+        let normalized = (world - self.center) / self.half_size();
+        Vec2::new((normalized.x + 1.0) * 0.5 * self.screen_size.x, (1.0 - normalized.y) * 0.5 * self.screen_size.y)
+    } /* world_to_screen */
+
+    // World to clip space matrix for shaders, column major 4x4; world z is ignored
+    pub fn view_projection(&self) -> [f32; 16] {
+        let scale = Vec2::new(1.0, 1.0) / self.half_size();
+        let translation = self.center * scale * -1.0;
+        [
+            scale.x, 0.0, 0.0, 0.0,
+            0.0, scale.y, 0.0, 0.0,
+            0.0, 0.0, 1.0, 0.0,
+            translation.x, translation.y, 0.0, 1.0,
+        ]
+    } /* view_projection */
+} /* impl View */
@@ -0,0 +1,151 @@
+/* Player weapons: fire rate, spread, multi-shot, piercing and magazines */
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum WeaponKind {
+    // Semi-automatic, one bullet per trigger pull
+    Pistol,
+    // Automatic, fires while trigger is held
+    Rifle,
+    // Fan of short lived pellets
+    Shotgun,
+    // Slow firing, bullets pass through enemies
+    Railgun,
+} /* WeaponKind */
+
+// Per-kind constants
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct WeaponStats {
+    // Seconds between two shots
+    pub fire_interval: f32,
+    // Fire while trigger is held, otherwise every shot needs new press
+    pub automatic: bool,
+    pub bullet_speed: f32,
+    pub bullet_lifetime: f32,
+    pub damage: f32,
+    // Bullets fired per shot
+    pub pellets: u32,
+    // Full cone angle (radians) bullets are randomly spread in
+    pub spread: f32,
+    // Number of enemies bullet passes through before despawning
+    pub pierce: u32,
+    // Number of times bullet ricochets off walls
+    pub bounces: u32,
+    // Shots per magazine
+    pub magazine: u32,
+    pub reload_duration: f32,
+} /* WeaponStats */
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Weapon {
+    pub kind: WeaponKind,
+    // Shots left in magazine
+    pub ammo: u32,
+    // Seconds until weapon can fire again
+    pub cooldown: f32,
+    // Seconds until reload finishes, 0 if not reloading
+    pub reload_time: f32,
+    // Trigger state on last update, semi-automatic weapons fire on press only
+    pub trigger_held: bool,
+} /* Weapon */
+
+pub const ALL_WEAPON_KINDS: [WeaponKind; 4] = [
+    WeaponKind::Pistol,
+    WeaponKind::Rifle,
+    WeaponKind::Shotgun,
+    WeaponKind::Railgun,
+];
+pub const WEAPON_COUNT: usize = ALL_WEAPON_KINDS.len();
+
+impl WeaponKind {
+    pub fn from_name(name: &str) -> Option<WeaponKind> {
+        match name {
+            "pistol" => Some(WeaponKind::Pistol),
+            "rifle" => Some(WeaponKind::Rifle),
+            "shotgun" => Some(WeaponKind::Shotgun),
+            "railgun" => Some(WeaponKind::Railgun),
+            _ => None,
+        }
+    } /* from_name */
+
+    pub const fn stats(&self) -> WeaponStats {
+        match self {
+            WeaponKind::Pistol => WeaponStats {
+                fire_interval: 0.15, automatic: false, bullet_speed: 1.0, bullet_lifetime: 3.0, damage: 10.0,
+                pellets: 1, spread: 0.0, pierce: 0, bounces: 0, magazine: 12, reload_duration: 0.8,
+            },
+            WeaponKind::Rifle => WeaponStats {
+                fire_interval: 0.08, automatic: true, bullet_speed: 1.6, bullet_lifetime: 2.0, damage: 7.0,
+                pellets: 1, spread: 0.08, pierce: 0, bounces: 0, magazine: 30, reload_duration: 1.5,
+            },
+            WeaponKind::Shotgun => WeaponStats {
+                fire_interval: 0.6, automatic: false, bullet_speed: 1.4, bullet_lifetime: 0.5, damage: 6.0,
+                pellets: 7, spread: 0.5, pierce: 0, bounces: 1, magazine: 6, reload_duration: 1.8,
+            },
+            WeaponKind::Railgun => WeaponStats {
+                fire_interval: 1.0, automatic: false, bullet_speed: 3.0, bullet_lifetime: 1.5, damage: 40.0,
+                pellets: 1, spread: 0.0, pierce: 8, bounces: 2, magazine: 3, reload_duration: 2.0,
+            },
+        }
+    } /* stats */
+} /* impl WeaponKind */
+
+impl Weapon {
+    pub fn new(kind: WeaponKind) -> Weapon {
+        Weapon { kind, ammo: kind.stats().magazine, cooldown: 0.0, reload_time: 0.0, trigger_held: false }
+    } /* new */
+
+    pub fn stats(&self) -> WeaponStats {
+        self.kind.stats()
+    } /* stats */
+
+    pub fn is_reloading(&self) -> bool {
+        self.reload_time > 0.0
+    } /* is_reloading */
+
+    // Start reload, if magazine isn't full already
+    pub fn reload(&mut self) {
+        if !self.is_reloading() && self.ammo < self.stats().magazine {
+            self.reload_time = self.stats().reload_duration;
+        }
+    } /* reload */
+
+    // Weapon put away, unfinished reload is lost
+    pub fn holster(&mut self) {
+        self.reload_time = 0.0;
+        self.trigger_held = false;
+    } /* holster */
+
+    // Advance timers by one tick, returns true if weapon fires this tick
+    pub fn update(&mut self, trigger: bool, delta_time: f32) -> bool {
+        let stats = self.stats();
+        let pressed = trigger && !self.trigger_held;
+        self.trigger_held = trigger;
+
+        self.cooldown = (self.cooldown - delta_time).max(0.0);
+        if self.is_reloading() {
+            self.reload_time -= delta_time;
+            if self.reload_time > 0.0 {
+                return false;
+            }
+            self.reload_time = 0.0;
+            self.ammo = stats.magazine;
+        }
+
+        if !(pressed || (trigger && stats.automatic)) || self.cooldown > 0.0 {
+            return false;
+        }
+
+        // Empty magazine is reloaded on next pull
+        if self.ammo == 0 {
+            self.reload();
+            return false;
+        }
+
+        self.ammo -= 1;
+        self.cooldown = stats.fire_interval;
+        if self.ammo == 0 {
+            self.reload();
+        }
+        true
+    } /* update */
+} /* impl Weapon */
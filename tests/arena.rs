@@ -0,0 +1,120 @@
+// Arena walls: bodies slide along them, bullets stop or ricochet
+
+mod common;
+
+use pixel_mobs_gun_rs::arena::Arena;
+use pixel_mobs_gun_rs::collision::Aabb;
+use pixel_mobs_gun_rs::game::{self, EnemyKind};
+
+type Vec2 = game::Vec2;
+
+fn walking(move_axis: Vec2) -> game::PlayerInput {
+    game::PlayerInput { move_axis, ..game::PlayerInput::new() }
+}
+
+#[test]
+fn player_stays_inside_bounds() {
+    let mut engine = game::Engine::new();
+    common::run_for(&mut engine, &walking(Vec2::new(1.0, 1.0)), 5.0);
+
+    let inner = engine.arena.bounds.expanded(-game::PLAYER_RADIUS);
+    assert!((engine.player.position - inner.max).length() < 1e-4);
+
+    // Knockback can't throw player out either
+    engine.player.knockback = Vec2::new(-100.0, 0.0);
+    common::run_for(&mut engine, &walking(Vec2::new(0.0, 0.0)), 1.0);
+    assert!((engine.player.position.x - inner.min.x).abs() < 1e-4);
+}
+
+#[test]
+fn player_slides_along_obstacle() {
+    let mut engine = game::Engine::new();
+    engine.arena.obstacles.push(Aabb::new(Vec2::new(0.2, -0.5), Vec2::new(0.4, 0.5)));
+
+    // Diagonal walk into obstacle side keeps vertical part of movement
+    common::run_for(&mut engine, &walking(Vec2::new(1.0, 0.5)), 0.5);
+    let position = engine.player.position;
+    assert!((position.x - (0.2 - game::PLAYER_RADIUS)).abs() < 1e-3);
+
+    common::run_for(&mut engine, &walking(Vec2::new(1.0, 0.5)), 0.5);
+    assert!(engine.player.position.y > position.y + 0.1);
+    assert!(engine.arena.is_free(engine.player.position, game::PLAYER_RADIUS * 0.99));
+}
+
+#[test]
+fn enemies_stay_out_of_walls() {
+    let mut engine = common::sturdy_engine();
+    engine.arena.obstacles.push(Aabb::from_center(Vec2::new(0.0, 0.0), Vec2::new(0.3, 0.3)));
+    // Player hides behind obstacle, enemies try to go straight through
+    engine.player.position = Vec2::new(-0.5, 0.0);
+    for y in [-0.1, 0.0, 0.1] {
+        engine.enemies.push(game::Enemy::new(EnemyKind::Brute, Vec2::new(1.0, y)));
+    }
+
+    for _ in 0..(3.0 / game::TICK_DURATION) as usize {
+        engine.update(&game::PlayerInput::new());
+        for enemy in &engine.enemies {
+            assert!(engine.arena.is_free(enemy.position, enemy.kind.radius() * 0.99));
+        }
+    }
+}
+
+#[test]
+fn wall_resolution_pushes_out_of_obstacle() {
+    let arena = Arena::with_obstacles(
+        Aabb::from_center(Vec2::new(0.0, 0.0), Vec2::new(1.0, 1.0)),
+        vec![Aabb::new(Vec2::new(-0.1, -0.1), Vec2::new(0.1, 0.5))],
+    );
+
+    // Deep inside: leaves through nearest face
+    let resolved = arena.resolve_circle(Vec2::new(0.08, 0.2), 0.05);
+    assert_eq!(resolved, Vec2::new(0.15, 0.2));
+    // Corner: pushed away diagonally
+    let resolved = arena.resolve_circle(Vec2::new(0.12, -0.12), 0.05);
+    assert!(arena.is_free(resolved, 0.0499));
+    assert!(resolved.x > 0.12 && resolved.y < -0.12);
+}
+
+#[test]
+fn bullets_stop_on_walls_without_bounces() {
+    // Enemy behind wall is safe
+    let mut engine = common::engine_with(EnemyKind::Brute, Vec2::new(0.8, 0.0));
+    engine.arena.obstacles.push(Aabb::from_center(Vec2::new(0.5, 0.0), Vec2::new(0.05, 0.5)));
+    engine.bullets.spawn(game::Bullet::new(Vec2::new(0.3, 0.0), Vec2::new(60.0, 0.0)));
+
+    engine.update(&game::PlayerInput::new());
+    assert!(engine.bullets.is_empty());
+    assert_eq!(engine.enemies[0].health, EnemyKind::Brute.max_health());
+}
+
+#[test]
+fn bouncing_bullets_ricochet() {
+    let mut engine = game::Engine::new();
+    let top = engine.arena.bounds.max.y;
+    let mut bullet = game::Bullet::new(Vec2::new(0.0, top - 0.05), Vec2::new(1.0, 1.0));
+    bullet.bounces = 1;
+    engine.bullets.spawn(bullet);
+
+    common::run_for(&mut engine, &game::PlayerInput::new(), 0.1);
+    let bullet = engine.bullets.as_slice()[0];
+    assert_eq!(bullet.bounces, 0);
+    assert_eq!(bullet.velocity, Vec2::new(1.0, -1.0));
+    assert!(bullet.position.y < top - game::BULLET_RADIUS);
+    assert!(bullet.position.x > 0.09);
+
+    // Second wall stops it
+    common::run_for(&mut engine, &game::PlayerInput::new(), 3.0);
+    assert!(engine.bullets.is_empty());
+}
+
+#[test]
+fn enemy_bullets_stop_on_obstacles() {
+    let mut engine = game::Engine::new();
+    engine.arena.obstacles.push(Aabb::from_center(Vec2::new(0.3, 0.0), Vec2::new(0.05, 0.3)));
+    engine.enemy_bullets.spawn(game::Bullet::new(Vec2::new(0.6, 0.0), Vec2::new(-1.0, 0.0)));
+
+    let health = engine.player.health;
+    common::run_for(&mut engine, &game::PlayerInput::new(), 1.0);
+    assert!(engine.enemy_bullets.is_empty());
+    assert_eq!(engine.player.health, health);
+}
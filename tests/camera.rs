@@ -0,0 +1,102 @@
+// Camera follow, bounds clamping and shake
+
+use pixel_mobs_gun_rs::camera::Camera;
+use pixel_mobs_gun_rs::collision::Aabb;
+use pixel_mobs_gun_rs::game;
+use pixel_mobs_gun_rs::view::View;
+
+type Vec2 = game::Vec2;
+
+const SCREEN: Vec2 = Vec2 { x: 800.0, y: 600.0 };
+
+fn large_bounds() -> Aabb {
+    Aabb::from_center(Vec2::new(0.0, 0.0), Vec2::new(10.0, 10.0))
+}
+
+fn run(camera: &mut Camera, target: Vec2, bounds: &Aabb, seconds: f32) {
+    for _ in 0..(seconds / game::TICK_DURATION) as usize {
+        camera.update(target, bounds, SCREEN, game::TICK_DURATION);
+    }
+}
+
+#[test]
+fn target_inside_dead_zone_doesnt_move_camera() {
+    let mut camera = Camera::new(Vec2::new(0.0, 0.0));
+    let inside = camera.config.dead_zone * 0.9;
+
+    run(&mut camera, inside, &large_bounds(), 1.0);
+    assert_eq!(camera.position, Vec2::new(0.0, 0.0));
+}
+
+#[test]
+fn camera_follows_smoothly_to_dead_zone_edge() {
+    let mut camera = Camera::new(Vec2::new(0.0, 0.0));
+    let target = Vec2::new(2.0, -1.0);
+
+    // Doesn't jump
+    camera.update(target, &large_bounds(), SCREEN, game::TICK_DURATION);
+    assert!(camera.position.x > 0.0 && camera.position.x < 0.5);
+
+    // Settles with target on dead zone edge
+    run(&mut camera, target, &large_bounds(), 3.0);
+    let expected = target - Vec2::new(camera.config.dead_zone.x, -camera.config.dead_zone.y);
+    assert!((camera.position - expected).length() < 1e-3);
+}
+
+#[test]
+fn camera_stays_inside_bounds() {
+    let bounds = Aabb::from_center(Vec2::new(0.0, 0.0), Vec2::new(1.5, 1.0));
+    let mut camera = Camera::new(Vec2::new(0.0, 0.0));
+
+    run(&mut camera, Vec2::new(5.0, 5.0), &bounds, 3.0);
+    let view = camera.view(SCREEN);
+    let half_size = view.half_size();
+    assert!((view.center.x + half_size.x - bounds.max.x).abs() < 1e-4);
+    assert!((view.center.y + half_size.y - bounds.max.y).abs() < 1e-4);
+
+    // Arena narrower than view is centered
+    camera.zoom = 0.5;
+    camera.snap(Vec2::new(1.0, 1.0), &bounds, SCREEN);
+    assert_eq!(camera.position, Vec2::new(0.0, 0.0));
+}
+
+#[test]
+fn trauma_shakes_and_decays() {
+    let mut camera = Camera::new(Vec2::new(0.0, 0.0));
+    let bounds = large_bounds();
+
+    camera.update(Vec2::new(0.0, 0.0), &bounds, SCREEN, game::TICK_DURATION);
+    assert_eq!(camera.view(SCREEN).center, camera.position);
+
+    camera.add_trauma(0.7);
+    camera.add_trauma(0.7);
+    assert_eq!(camera.trauma, 1.0);
+    camera.update(Vec2::new(0.0, 0.0), &bounds, SCREEN, game::TICK_DURATION);
+    let shake = camera.view(SCREEN).center - camera.position;
+    assert!(shake.length2() > 0.0);
+    assert!(shake.x.abs() <= camera.config.max_shake_offset && shake.y.abs() <= camera.config.max_shake_offset);
+    // Shake moves only view, not camera, nor view aim goes through
+    assert_eq!(camera.position, Vec2::new(0.0, 0.0));
+    assert_eq!(camera.steady_view(SCREEN).center, camera.position);
+
+    let calm_down = 1.0 / camera.config.trauma_decay + 0.1;
+    run(&mut camera, Vec2::new(0.0, 0.0), &bounds, calm_down);
+    assert_eq!(camera.trauma, 0.0);
+    assert_eq!(camera.view(SCREEN).center, camera.position);
+}
+
+#[test]
+fn view_projection_maps_view_to_clip_space() {
+    let mut view = View::new(1280, 720);
+    view.center = Vec2::new(0.5, -0.25);
+    view.zoom = 2.0;
+    let matrix = view.view_projection();
+    let to_clip = |world: Vec2| Vec2::new(matrix[0] * world.x + matrix[12], matrix[5] * world.y + matrix[13]);
+
+    assert!(to_clip(view.center).length() < 1e-6);
+    // Top right corner of window is (1, 1) in clip space
+    let corner = view.screen_to_world(Vec2::new(1280.0, 0.0));
+    assert!((to_clip(corner) - Vec2::new(1.0, 1.0)).length() < 1e-5);
+    // Zoom shows less of world
+    assert!((view.half_size().y - 0.5).abs() < 1e-6);
+}
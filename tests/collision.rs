@@ -0,0 +1,144 @@
+// Collision primitives and their use by engine
+
+use pixel_mobs_gun_rs::collision::{Aabb, Circle};
+use pixel_mobs_gun_rs::game;
+
+type Vec2 = game::Vec2;
+
+#[test]
+fn circles_intersect_by_radius_sum() {
+    let a = Circle::new(Vec2::new(0.0, 0.0), 0.5);
+
+    assert!(a.intersects(&Circle::new(Vec2::new(0.7, 0.0), 0.25)));
+    assert!(!a.intersects(&Circle::new(Vec2::new(0.8, 0.0), 0.25)));
+    assert!(!a.intersects(&Circle::new(Vec2::new(0.6, 0.6), 0.3)));
+
+    assert!(a.contains(Vec2::new(0.3, 0.3)));
+    assert!(!a.contains(Vec2::new(0.4, 0.4)));
+}
+
+#[test]
+fn aabb_tests() {
+    let aabb = Aabb::from_center(Vec2::new(1.0, 1.0), Vec2::new(0.5, 0.25));
+
+    assert_eq!(aabb.min, Vec2::new(0.5, 0.75));
+    assert_eq!(aabb.center(), Vec2::new(1.0, 1.0));
+    assert!(aabb.contains(Vec2::new(1.4, 1.2)));
+    assert!(!aabb.contains(Vec2::new(1.4, 1.3)));
+
+    assert!(aabb.intersects(&Aabb::new(Vec2::new(1.4, 0.0), Vec2::new(2.0, 0.8))));
+    assert!(!aabb.intersects(&Aabb::new(Vec2::new(1.5, 0.0), Vec2::new(2.0, 0.8))));
+
+    assert_eq!(aabb.closest_point(Vec2::new(3.0, 0.0)), Vec2::new(1.5, 0.75));
+    assert!(Circle::new(Vec2::new(1.7, 1.0), 0.25).intersects_aabb(&aabb));
+    // Close to corner by axes, but not by distance
+    assert!(!Circle::new(Vec2::new(1.7, 1.45), 0.25).intersects_aabb(&aabb));
+}
+
+#[test]
+fn sweep_finds_time_of_impact() {
+    let target = Circle::new(Vec2::new(1.0, 0.0), 0.1);
+    let moving = Circle::new(Vec2::new(0.0, 0.0), 0.1);
+
+    let t = moving.sweep(Vec2::new(2.0, 0.0), &target).unwrap();
+    assert!((t - 0.4).abs() < 1e-5);
+
+    // Too short, passing by and moving away
+    assert!(moving.sweep(Vec2::new(0.5, 0.0), &target).is_none());
+    assert!(moving.sweep(Vec2::new(2.0, 1.0), &target).is_none());
+    assert!(moving.sweep(Vec2::new(-2.0, 0.0), &target).is_none());
+
+    // Already overlapping
+    assert_eq!(Circle::new(Vec2::new(0.9, 0.0), 0.1).sweep(Vec2::new(0.0, 0.0), &target), Some(0.0));
+}
+
+#[test]
+fn fast_bullet_does_not_tunnel_through_enemy() {
+    let mut engine = game::Engine::new();
+    engine.enemies.push(game::Enemy::new(game::EnemyKind::Chaser, Vec2::new(0.5, 0.0)));
+
+    // Bullet covers more than enemy width in one tick
+    let enemy_size = game::EnemyKind::Chaser.stats().size;
+    let speed = enemy_size * 3.0 / game::TICK_DURATION;
+    engine.bullets.spawn(game::Bullet::new(Vec2::new(0.5 - enemy_size * 1.5, 0.0), Vec2::new(speed, 0.0)));
+
+    engine.update(&game::PlayerInput::new());
+
+    assert!(engine.enemies.is_empty());
+}
+
+#[test]
+fn player_contact_uses_radius_sum() {
+    let touching = game::PLAYER_RADIUS + game::EnemyKind::Chaser.radius();
+
+    let mut engine = game::Engine::new();
+    engine.enemies.push(game::Enemy::new(game::EnemyKind::Chaser, Vec2::new(touching * 0.9, 0.0)));
+    engine.update(&game::PlayerInput::new());
+    assert!(engine.player.health < 100.0);
+
+    let mut engine = game::Engine::new();
+    engine.enemies.push(game::Enemy::new(game::EnemyKind::Chaser, Vec2::new(touching * 1.5, 0.0)));
+    engine.update(&game::PlayerInput::new());
+    assert_eq!(engine.player.health, 100.0);
+}
+
+#[test]
+fn bullets_despawn() {
+    let mut engine = game::Engine::new();
+    engine.enemies.push(game::Enemy::new(game::EnemyKind::Chaser, Vec2::new(0.5, 0.0)));
+    engine.enemies.push(game::Enemy::new(game::EnemyKind::Chaser, Vec2::new(0.8, 0.0)));
+
+    // Hitting bullet kills only first enemy on its path
+    engine.bullets.spawn(game::Bullet::new(Vec2::new(0.3, 0.0), Vec2::new(60.0, 0.0)));
+    // Leaving play field
+    engine.bullets.spawn(game::Bullet::new(Vec2::new(0.0, engine.arena.bounds.max.y - 0.001), Vec2::new(0.0, 1.0)));
+    // Expiring
+    let mut expiring = game::Bullet::new(Vec2::new(0.0, -0.5), Vec2::new(0.0, -0.1));
+    expiring.lifetime = game::TICK_DURATION * 0.5;
+    engine.bullets.spawn(expiring);
+    // Staying alive
+    engine.bullets.spawn(game::Bullet::new(Vec2::new(-0.5, 0.0), Vec2::new(-0.1, 0.0)));
+
+    engine.update(&game::PlayerInput::new());
+
+    assert_eq!(engine.enemies.len(), 1);
+    assert!(engine.enemies[0].position.x > 0.7);
+    assert_eq!(engine.bullets.len(), 1);
+}
+
+#[test]
+fn expiring_bullets_hit_on_last_step() {
+    let mut engine = game::Engine::new();
+    engine.enemies.push(game::Enemy::new(game::EnemyKind::Chaser, Vec2::new(0.5, 0.0)));
+
+    // Both bullets reach their target only on step they expire in
+    let speed = 0.4 / game::TICK_DURATION;
+    let mut expiring = game::Bullet::new(Vec2::new(0.3, 0.0), Vec2::new(speed, 0.0));
+    expiring.lifetime = game::TICK_DURATION * 0.5;
+    engine.bullets.spawn(expiring);
+    let mut expiring = game::Bullet::new(Vec2::new(0.0, -0.4), Vec2::new(0.0, speed));
+    expiring.lifetime = game::TICK_DURATION * 0.5;
+    engine.enemy_bullets.spawn(expiring);
+
+    engine.update(&game::PlayerInput::new());
+
+    assert!(engine.enemies.is_empty() || engine.enemies[0].health < game::EnemyKind::Chaser.stats().max_health);
+    assert!(engine.player.health < game::PLAYER_MAX_HEALTH);
+    assert!(engine.bullets.is_empty());
+    assert!(engine.enemy_bullets.is_empty());
+}
+
+#[test]
+fn sustained_fire_is_bounded() {
+    let mut engine = game::Engine::new();
+    let input = game::PlayerInput { aim: Vec2::new(1.0, 0.0), fire: true, ..game::PlayerInput::new() };
+
+    for _ in 0..3000 {
+        engine.update(&input);
+    }
+
+    // Bullets reach the wall in ARENA_HALF_WIDTH / BULLET_SPEED seconds, one is fired each tick
+    let alive_limit = (game::ARENA_HALF_WIDTH / game::BULLET_SPEED / game::TICK_DURATION) as usize + 2;
+    assert!(engine.bullets.len() <= alive_limit);
+    assert!(engine.bullets.len() <= engine.bullets.capacity());
+}
@@ -0,0 +1,37 @@
+// Engine helpers shared by tests, every test file uses only some of them
+#![allow(dead_code)]
+
+use pixel_mobs_gun_rs::game::{self, EnemyKind, WeaponKind};
+use pixel_mobs_gun_rs::weapon;
+
+type Vec2 = game::Vec2;
+
+// Engine runs for given time, same input every tick
+pub fn run_for(engine: &mut game::Engine, input: &game::PlayerInput, seconds: f32) {
+    for _ in 0..(seconds / game::TICK_DURATION) as usize {
+        engine.update(input);
+    }
+} /* run_for */
+
+// Engine whose player survives whole test, so behaviours are tested and not damage
+pub fn sturdy_engine() -> game::Engine {
+    let mut engine = game::Engine::new();
+    engine.player.health = 1.0e9;
+    engine
+} /* sturdy_engine */
+
+// Sturdy engine with single enemy
+pub fn engine_with(kind: EnemyKind, position: Vec2) -> game::Engine {
+    let mut engine = sturdy_engine();
+    engine.enemies.push(game::Enemy::new(kind, position));
+    engine
+} /* engine_with */
+
+// Engine whose player holds weapon of given kind
+pub fn armed_engine(kind: WeaponKind) -> game::Engine {
+    let mut engine = game::Engine::new();
+    let slot = weapon::ALL_WEAPON_KINDS.iter().position(|weapon| *weapon == kind).unwrap();
+    engine.update(&game::PlayerInput { switch_weapon: Some(slot), ..game::PlayerInput::new() });
+    assert_eq!(engine.player.weapon().kind, kind);
+    engine
+} /* armed_engine */
@@ -0,0 +1,72 @@
+// Enemy archetype behaviours
+
+mod common;
+
+use pixel_mobs_gun_rs::enemy;
+use pixel_mobs_gun_rs::game::{self, EnemyKind, EnemyPhase};
+
+type Vec2 = game::Vec2;
+
+#[test]
+fn every_kind_has_name_and_fits_broad_phase() {
+    for kind in enemy::ALL_ENEMY_KINDS {
+        let name = format!("{kind:?}").to_lowercase();
+
+        assert_eq!(EnemyKind::from_name(&name), Some(kind));
+        assert!(kind.stats().size <= enemy::MAX_ENEMY_SIZE);
+        assert!(kind.max_health() > 0.0);
+    }
+}
+
+#[test]
+fn orbiter_circles_at_distance() {
+    let mut engine = common::engine_with(EnemyKind::Orbiter, Vec2::new(0.9, 0.0));
+    common::run_for(&mut engine, &game::PlayerInput::new(), 3.0);
+
+    let start_angle = engine.enemies[0].position.y.atan2(engine.enemies[0].position.x);
+    common::run_for(&mut engine, &game::PlayerInput::new(), 0.5);
+    let orbiter = engine.enemies[0];
+
+    assert!((orbiter.position.length() - enemy::ORBIT_DISTANCE).abs() < 0.05);
+    assert!((orbiter.position.y.atan2(orbiter.position.x) - start_angle).abs() > 0.1);
+}
+
+#[test]
+fn dasher_winds_up_and_charges() {
+    let mut engine = common::engine_with(EnemyKind::Dasher, Vec2::new(enemy::DASH_TRIGGER_DISTANCE * 0.9, 0.0));
+
+    common::run_for(&mut engine, &game::PlayerInput::new(), game::TICK_DURATION * 2.0);
+    assert_eq!(engine.enemies[0].phase, EnemyPhase::WindUp);
+
+    // Stands still during wind up
+    let position = engine.enemies[0].position;
+    common::run_for(&mut engine, &game::PlayerInput::new(), enemy::DASH_WIND_UP * 0.5);
+    assert_eq!(engine.enemies[0].position, position);
+
+    common::run_for(&mut engine, &game::PlayerInput::new(), enemy::DASH_WIND_UP * 0.5 + game::TICK_DURATION * 2.0);
+    assert_eq!(engine.enemies[0].phase, EnemyPhase::Dashing);
+    assert!(engine.enemies[0].velocity.length() > EnemyKind::Dasher.stats().speed * 2.0);
+}
+
+#[test]
+fn splitter_splits_on_death() {
+    let mut engine = common::engine_with(EnemyKind::Splitter, Vec2::new(0.5, 0.0));
+    engine.enemies[0].health = game::BULLET_DAMAGE;
+    engine.bullets.spawn(game::Bullet::new(Vec2::new(0.3, 0.0), Vec2::new(60.0, 0.0)));
+
+    engine.update(&game::PlayerInput::new());
+
+    assert_eq!(engine.enemies.len(), enemy::SPLIT_COUNT as usize);
+    assert!(engine.enemies.iter().all(|enemy| enemy.kind == EnemyKind::Splitling));
+}
+
+#[test]
+fn shooter_bullets_hurt_player() {
+    let mut engine = common::engine_with(EnemyKind::Shooter, Vec2::new(enemy::SHOOTER_DISTANCE, 0.0));
+    let health = engine.player.health;
+
+    common::run_for(&mut engine, &game::PlayerInput::new(), enemy::SHOOTER_FIRE_INTERVAL + enemy::SHOOTER_DISTANCE / game::ENEMY_BULLET_SPEED + 0.2);
+
+    assert_eq!(engine.player.health, health - game::ENEMY_BULLET_DAMAGE);
+    assert!(engine.enemy_bullets.is_empty());
+}
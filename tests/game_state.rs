@@ -0,0 +1,83 @@
+// Player damage, death and game state transitions
+
+use pixel_mobs_gun_rs::game::{self, GameEvent, GameState};
+
+type Vec2 = game::Vec2;
+
+fn idle() -> game::PlayerInput {
+    game::PlayerInput::new()
+}
+
+#[test]
+fn hit_gives_invulnerability_and_knockback() {
+    let mut engine = game::Engine::new();
+    engine.enemies.push(game::Enemy::new(game::EnemyKind::Brute, Vec2::new(game::EnemyKind::Brute.radius(), 0.0)));
+
+    engine.update(&idle());
+
+    assert_eq!(engine.player.health, game::PLAYER_MAX_HEALTH - game::ENEMY_CONTACT_DAMAGE);
+    assert!(engine.player.is_invulnerable());
+    assert!(engine.player.knockback.x < 0.0);
+    assert!(engine.events.iter().any(|event| matches!(event, GameEvent::PlayerHit { .. })));
+
+    // No more damage while invulnerable, even if enemy catches up
+    let ticks = (game::INVULNERABILITY_DURATION / game::TICK_DURATION) as usize - 2;
+    for _ in 0..ticks {
+        engine.player.position = engine.enemies[0].position;
+        engine.update(&idle());
+    }
+    assert_eq!(engine.player.health, game::PLAYER_MAX_HEALTH - game::ENEMY_CONTACT_DAMAGE);
+}
+
+#[test]
+fn death_leads_to_game_over() {
+    let mut engine = game::Engine::new();
+    engine.enemies.push(game::Enemy::new(game::EnemyKind::Brute, Vec2::new(0.0, 0.0)));
+    engine.player.health = game::ENEMY_CONTACT_DAMAGE;
+
+    engine.update(&idle());
+    assert_eq!(engine.state(), GameState::PlayerDead);
+    assert_eq!(engine.player.health, 0.0);
+    assert!(engine.events.iter().any(|event| matches!(event, GameEvent::PlayerDied { .. })));
+
+    // Dead player ignores input
+    let position = engine.player.position;
+    let input = game::PlayerInput { move_axis: Vec2::new(1.0, 0.0), aim: Vec2::new(1.0, 0.0), fire: true, ..game::PlayerInput::new() };
+    engine.player.knockback = Vec2::new(0.0, 0.0);
+    engine.update(&input);
+    assert_eq!(engine.player.position, position);
+    assert!(engine.bullets.is_empty());
+
+    let mut game_over_events = 0;
+    for _ in 0..(game::DEATH_DURATION / game::TICK_DURATION) as usize + 2 {
+        engine.update(&idle());
+        game_over_events += engine.events.iter().filter(|event| **event == GameEvent::GameOver).count();
+    }
+    assert_eq!(engine.state(), GameState::GameOver);
+    assert_eq!(game_over_events, 1);
+
+    // Game over freezes simulation
+    let tick = engine.tick();
+    engine.update(&idle());
+    assert_eq!(engine.tick(), tick);
+    assert!(!engine.pause());
+}
+
+#[test]
+fn pause_freezes_simulation() {
+    let mut engine = game::Engine::new();
+    engine.enemies.push(game::Enemy::new(game::EnemyKind::Chaser, Vec2::new(0.5, 0.5)));
+
+    assert!(engine.pause());
+    assert_eq!(engine.state(), GameState::Paused);
+
+    let enemy_position = engine.enemies[0].position;
+    engine.update(&idle());
+    assert_eq!(engine.enemies[0].position, enemy_position);
+    assert_eq!(engine.tick(), 0);
+
+    assert!(engine.toggle_pause());
+    assert_eq!(engine.state(), GameState::Playing);
+    engine.update(&idle());
+    assert_eq!(engine.tick(), 1);
+}
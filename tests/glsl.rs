@@ -0,0 +1,453 @@
+// Shader build errors, driver log mapping and offline shader validation
+
+use glsl_lang::ast;
+use glsl_lang::parse::DefaultParse;
+use glsl_lang::transpiler::glsl::{show_type_specifier_non_array, FormattingState};
+use glsl_lang::visitor::{Host, Visit, Visitor};
+use pixel_mobs_gun_rs::glsl::{self, ProgramSources, ShaderError, ShaderStage};
+use std::collections::{BTreeMap, HashMap};
+
+const ENTITY_VERT: &str = include_str!("../assets/shaders/entity.vert");
+const ENTITY_GEOM: &str = include_str!("../assets/shaders/entity.geom");
+const ENTITY_FRAG: &str = include_str!("../assets/shaders/entity.frag");
+
+// Offline check: every stage is parsed by glsl-lang, interfaces between stages are matched here like linker does
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Storage {
+    In,
+    Out,
+    Uniform,
+}
+
+// Global in, out or uniform variable of shader
+#[derive(Clone, Debug)]
+struct Variable {
+    storage: Storage,
+    type_name: String,
+    name: String,
+    // Declared with [], e.g. geometry shader inputs
+    array: bool,
+    location: Option<i32>,
+    line: usize,
+}
+
+// Everything shader shares with other stages and the game; interface blocks aren't matched
+#[derive(Debug, Default)]
+struct ShaderInterface {
+    // E.g. "330 core"
+    version: String,
+    variables: Vec<Variable>,
+    // gl_Position is assigned somewhere, no data flow is analysed
+    writes_position: bool,
+}
+
+impl ShaderInterface {
+    fn variables(&self, storage: Storage) -> impl Iterator<Item = &Variable> {
+        self.variables.iter().filter(move |variable| variable.storage == storage)
+    }
+
+    fn variable(&self, storage: Storage, name: &str) -> Option<&Variable> {
+        self.variables(storage).find(|variable| variable.name == name)
+    }
+}
+
+// Looks for assignments to gl_Position or its components
+struct PositionWrites(bool);
+
+impl Visitor for PositionWrites {
+    fn visit_expr(&mut self, expr: &ast::Expr) -> Visit {
+        if let ast::ExprData::Assignment(target, _, _) = &**expr {
+            let mut target = target;
+            while let ast::ExprData::Dot(inner, _) | ast::ExprData::Bracket(inner, _) = &***target {
+                target = inner;
+            }
+            self.0 |= matches!(&***target, ast::ExprData::Variable(name) if name.as_str() == "gl_Position");
+        }
+        Visit::Children
+    }
+}
+
+fn storage(qualifier: &ast::TypeQualifier) -> Option<Storage> {
+    qualifier.qualifiers.iter().find_map(|specifier| match &**specifier {
+        ast::TypeQualifierSpecData::Storage(storage) => match **storage {
+            ast::StorageQualifierData::In => Some(Storage::In),
+            ast::StorageQualifierData::Out => Some(Storage::Out),
+            ast::StorageQualifierData::Uniform => Some(Storage::Uniform),
+            _ => None,
+        },
+        _ => None,
+    })
+}
+
+fn location(qualifier: &ast::TypeQualifier) -> Option<i32> {
+    let layouts = qualifier.qualifiers.iter().filter_map(|specifier| match &**specifier {
+        ast::TypeQualifierSpecData::Layout(layout) => Some(layout),
+        _ => None,
+    });
+    layouts.flat_map(|layout| &layout.ids).find_map(|id| match &**id {
+        ast::LayoutQualifierSpecData::Identifier(name, Some(value)) if name.as_str() == "location" => match ***value {
+            ast::ExprData::IntConst(location) => Some(location),
+            _ => None,
+        },
+        _ => None,
+    })
+}
+
+// Global declarations of shader, error has log in driver format so it is annotated like real one;
+// errors about whole shader have no location
+fn parse_interface(stage: ShaderStage, source: &str) -> Result<ShaderInterface, ShaderError> {
+    let error = |line: usize, message: String| {
+        let log = match line {
+            0 => format!("error: {message}\n"),
+            _ => glsl::annotate_log(&format!("0:{line}: error: {message}"), source),
+        };
+        ShaderError::Compile { stage, log }
+    };
+    let unit = ast::TranslationUnit::parse(source).map_err(|parse_error| {
+        // Parser lists every token it could take, drivers don't
+        let message = parse_error.inner().to_string();
+        let message = message.split(", expected").next().unwrap_or_default().to_string();
+        error(parse_error.line() as usize + 1, message)
+    })?;
+    let line_of = |span: Option<glsl_lang::ast::NodeSpan>| {
+        span.map_or(0, |span| source[..usize::from(span.start().offset)].matches('\n').count() + 1)
+    };
+
+    let mut interface = ShaderInterface::default();
+    let mut has_main = false;
+    for declaration in &unit.0 {
+        match &**declaration {
+            ast::ExternalDeclarationData::Preprocessor(directive) => {
+                if let ast::PreprocessorData::Version(version) = &**directive {
+                    let profile = version.profile.as_ref().map(|profile| match **profile {
+                        ast::PreprocessorVersionProfileData::Core => " core",
+                        ast::PreprocessorVersionProfileData::Compatibility => " compatibility",
+                        ast::PreprocessorVersionProfileData::Es => " es",
+                    });
+                    interface.version = format!("{}{}", version.version, profile.unwrap_or_default());
+                }
+            }
+            ast::ExternalDeclarationData::FunctionDefinition(function) => has_main |= function.prototype.name.as_str() == "main",
+            ast::ExternalDeclarationData::Declaration(declaration) => {
+                let ast::DeclarationData::InitDeclaratorList(list) = &**declaration else {
+                    continue;
+                };
+                let Some((qualifier, storage)) = list.head.ty.qualifier.as_ref().and_then(|qualifier| Some((qualifier, storage(qualifier)?))) else {
+                    continue;
+                };
+                let mut type_name = String::new();
+                show_type_specifier_non_array(&mut type_name, &list.head.ty.ty.ty, &mut FormattingState::default()).unwrap();
+                let type_array = list.head.ty.ty.array_specifier.is_some();
+
+                let names = list.head.name.iter()
+                    .map(|name| (name, list.head.array_specifier.is_some()))
+                    .chain(list.tail.iter().map(|declarator| (&declarator.ident.ident, declarator.ident.array_spec.is_some())));
+                for (name, array) in names {
+                    interface.variables.push(Variable {
+                        storage,
+                        type_name: type_name.clone(),
+                        name: name.as_str().to_string(),
+                        array: array || type_array,
+                        location: location(qualifier),
+                        line: line_of(name.span),
+                    });
+                }
+            }
+        }
+    }
+
+    if interface.version.is_empty() {
+        return Err(error(0, "no #version directive".to_string()));
+    }
+    if !has_main {
+        return Err(error(0, "no main function".to_string()));
+    }
+    let mut position_writes = PositionWrites(false);
+    unit.visit(&mut position_writes);
+    interface.writes_position = position_writes.0;
+    Ok(interface)
+}
+
+fn describe(variable: &Variable) -> String {
+    format!("{}{}", variable.type_name, if variable.array { "[]" } else { "" })
+}
+
+// Check program without GPU: every stage parses, uniforms have same type in all stages
+// and inputs of every stage match outputs of previous one
+fn validate(sources: &ProgramSources) -> Result<Vec<(ShaderStage, ShaderInterface)>, ShaderError> {
+    let mut interfaces = Vec::<(ShaderStage, ShaderInterface)>::new();
+    for (stage, source) in sources.stages() {
+        let interface = parse_interface(stage, source)?;
+        let missing_output = match stage {
+            ShaderStage::Vertex | ShaderStage::Geometry => (!interface.writes_position).then_some("gl_Position is never written"),
+            ShaderStage::Fragment => interface.variables(Storage::Out).next().is_none().then_some("no outputs"),
+        };
+        if let Some(message) = missing_output {
+            return Err(ShaderError::Compile { stage, log: format!("error: {message}\n") });
+        }
+        interfaces.push((stage, interface));
+    }
+    let link_error = |message: String| Err(ShaderError::Link { log: format!("error: {message}\n") });
+
+    let (first_stage, first) = &interfaces[0];
+    for (stage, interface) in &interfaces[1..] {
+        if interface.version != first.version {
+            return link_error(format!("{stage} shader version {} differs from {first_stage} shader version {}", interface.version, first.version));
+        }
+    }
+
+    let mut uniforms = HashMap::<&str, (ShaderStage, &Variable)>::new();
+    for (stage, interface) in &interfaces {
+        for uniform in interface.variables(Storage::Uniform) {
+            match uniforms.get(uniform.name.as_str()) {
+                Some((other_stage, other)) if (other.type_name != uniform.type_name || other.array != uniform.array) => {
+                    return link_error(format!(
+                        "uniform '{}' is {} in {other_stage} shader, but {} in {stage} shader", uniform.name, describe(other), describe(uniform)
+                    ));
+                }
+                Some(_) => {}
+                None => {
+                    uniforms.insert(&uniform.name, (*stage, uniform));
+                }
+            }
+        }
+    }
+
+    for pair in interfaces.windows(2) {
+        let ((previous_stage, previous), (stage, interface)) = (&pair[0], &pair[1]);
+        for input in interface.variables(Storage::In) {
+            let Some(output) = previous.variable(Storage::Out, &input.name) else {
+                return link_error(format!("{stage} shader input '{}' has no matching {previous_stage} shader output", input.name));
+            };
+            // Geometry shader gets every output of all vertices of primitive
+            let array_expected = *stage == ShaderStage::Geometry || output.array;
+            if output.type_name != input.type_name || input.array != array_expected {
+                return link_error(format!(
+                    "{stage} shader input '{}' is {}, but {previous_stage} shader output is {}", input.name, describe(input), describe(output)
+                ));
+            }
+        }
+    }
+    Ok(interfaces)
+}
+
+fn entity_sources() -> ProgramSources<'static> {
+    ProgramSources { vertex: ENTITY_VERT, geometry: Some(ENTITY_GEOM), fragment: ENTITY_FRAG }
+}
+
+fn assert_link_error(sources: &ProgramSources, expected: &str) {
+    match validate(sources) {
+        Err(ShaderError::Link { log }) => assert!(log.contains(expected), "{log:?} doesn't mention {expected:?}"),
+        other => panic!("link error expected, got {other:?}"),
+    }
+}
+
+const SOURCE: &str = "#version 330 core
+out vec4 out_color;
+
+void main(void) {
+    out_color = vec4(color, 1);
+}
+";
+
+#[test]
+fn log_line_numbers_of_known_drivers() {
+    assert_eq!(glsl::log_line_number("0:5(22): error: `color' undeclared"), Some(5));
+    assert_eq!(glsl::log_line_number("0(5) : error C1008: undefined variable \"color\""), Some(5));
+    assert_eq!(glsl::log_line_number("ERROR: 0:5: 'color' : undeclared identifier"), Some(5));
+    assert_eq!(glsl::log_line_number("WARNING: 0:12: extension not supported"), Some(12));
+
+    assert_eq!(glsl::log_line_number("ERROR: 1 compilation errors.  No code generated."), None);
+    assert_eq!(glsl::log_line_number("error: linking failed"), None);
+    assert_eq!(glsl::log_line_number(""), None);
+}
+
+#[test]
+fn log_is_annotated_with_source_lines() {
+    let log = "0:5(22): error: `color' undeclared\n0:99(1): error: past end\nerror: summary\n\0";
+    let annotated = glsl::annotate_log(log, SOURCE);
+
+    assert_eq!(
+        annotated,
+        "0:5(22): error: `color' undeclared\n    5 | out_color = vec4(color, 1);\n0:99(1): error: past end\nerror: summary\n"
+    );
+}
+
+#[test]
+fn errors_name_failed_stage() {
+    let error = ShaderError::Compile { stage: ShaderStage::Geometry, log: "0:1(1): error\n".to_string() };
+    assert!(error.to_string().starts_with("geometry shader compilation failed:\n0:1(1)"));
+
+    assert_eq!(ShaderError::MissingStage(ShaderStage::Fragment).to_string(), "shader program has no fragment shader");
+    assert!(ShaderError::Link { log: "error: varying mismatch".to_string() }.to_string().ends_with("varying mismatch"));
+}
+
+#[test]
+fn every_shader_in_assets_is_valid() {
+    // Programs are shader files with same name, e.g. entity.vert, entity.geom and entity.frag
+    let mut programs = BTreeMap::<String, HashMap<ShaderStage, String>>::new();
+    let directory = concat!(env!("CARGO_MANIFEST_DIR"), "/assets/shaders");
+    for entry in std::fs::read_dir(directory).unwrap() {
+        let path = entry.unwrap().path();
+        let extension = path.extension().and_then(|extension| extension.to_str()).unwrap_or_default();
+        let stage = ShaderStage::from_extension(extension).unwrap_or_else(|| panic!("{path:?} is no shader"));
+        let name = path.file_stem().unwrap().to_string_lossy().into_owned();
+        programs.entry(name).or_default().insert(stage, std::fs::read_to_string(&path).unwrap());
+    }
+    assert!(programs.contains_key("entity"));
+
+    for (name, stages) in &programs {
+        let stage = |stage| stages.get(&stage).map(String::as_str);
+        let sources = ProgramSources {
+            vertex: stage(ShaderStage::Vertex).unwrap_or_else(|| panic!("{name} has no vertex shader")),
+            geometry: stage(ShaderStage::Geometry),
+            fragment: stage(ShaderStage::Fragment).unwrap_or_else(|| panic!("{name} has no fragment shader")),
+        };
+        if let Err(error) = validate(&sources) {
+            panic!("{name} shader is invalid: {error}");
+        }
+    }
+}
+
+#[test]
+fn entity_shader_interface() {
+    let interfaces = validate(&entity_sources()).unwrap();
+    let stages: Vec<ShaderStage> = interfaces.iter().map(|(stage, _)| *stage).collect();
+    assert_eq!(stages, [ShaderStage::Vertex, ShaderStage::Geometry, ShaderStage::Fragment]);
+
+    let vertex = &interfaces[0].1;
+    assert_eq!(vertex.version, "330 core");
+    assert_eq!(vertex.variable(Storage::In, "position").unwrap().location, Some(0));
+    // Everything game sets is declared somewhere
+    for uniform in ["point_size", "point_color", "view_projection"] {
+        assert!(interfaces.iter().any(|(_, interface)| interface.variable(Storage::Uniform, uniform).is_some()), "{uniform} isn't declared");
+    }
+    assert!(interfaces[1].1.variable(Storage::In, "color").unwrap().array);
+}
+
+#[test]
+fn swapped_stages_are_rejected() {
+    // Fragment shader given as vertex one, as it once was
+    let swapped = ProgramSources { vertex: ENTITY_FRAG, geometry: Some(ENTITY_GEOM), fragment: ENTITY_VERT };
+    match validate(&swapped) {
+        Err(ShaderError::Compile { stage: ShaderStage::Vertex, log }) => assert!(log.contains("gl_Position")),
+        other => panic!("vertex shader error expected, got {other:?}"),
+    }
+
+    let without_geometry = ProgramSources { geometry: None, ..entity_sources() };
+    assert_link_error(&without_geometry, "fragment shader input 'gs_color' has no matching vertex shader output");
+}
+
+#[test]
+fn mismatched_declarations_are_rejected() {
+    let geometry = ENTITY_GEOM.replace("uniform vec2 point_size;\nuniform mat4 view_projection;", "uniform vec2 point_size;\nuniform mat3 view_projection;");
+    assert_link_error(&ProgramSources { geometry: Some(&geometry), ..entity_sources() }, "uniform 'view_projection' is mat4 in vertex shader, but mat3 in geometry shader");
+
+    let geometry = ENTITY_GEOM.replace("in vec3 color[];", "in vec4 color[];");
+    assert_link_error(&ProgramSources { geometry: Some(&geometry), ..entity_sources() }, "geometry shader input 'color' is vec4[], but vertex shader output is vec3");
+
+    let geometry = ENTITY_GEOM.replace("in vec3 color[];", "in vec3 color;");
+    assert_link_error(&ProgramSources { geometry: Some(&geometry), ..entity_sources() }, "input 'color' is vec3, but");
+
+    let fragment = ENTITY_FRAG.replace("#version 330 core", "#version 410 core");
+    assert_link_error(&ProgramSources { fragment: &fragment, ..entity_sources() }, "fragment shader version 410 core differs");
+}
+
+#[test]
+fn syntax_errors_point_to_source_line() {
+    let cases = [
+        // Missing closing brace of main
+        (ENTITY_FRAG.replacen("}", "", 1), "0:8: error: unexpected end of input\n    8 | out_color = vec4(gs_color, 0);"),
+        (ENTITY_FRAG.replace("in vec3 gs_color;", "in vex3 gs_color;"), "0:5: error: unexpected identifier `gs_color`"),
+        (ENTITY_FRAG.replace("in vec3 gs_color;", "in vec3 gs_color"), "0:7: error: unexpected type name `void`\n    7 | void main(void) {"),
+        // Function bodies are parsed too
+        (ENTITY_FRAG.replace("vec4(gs_color, 0)", "vec4(gs_color, 0) +"), "0:8: error: unexpected `;`"),
+        (ENTITY_FRAG.replace("#version 330 core", ""), "error: no #version directive"),
+        (ENTITY_FRAG.replace("main", "entry"), "error: no main function"),
+        (format!("{ENTITY_FRAG}/* unterminated"), "0:10: error: invalid token\n   10 | /* unterminated"),
+    ];
+    for (fragment, expected) in &cases {
+        match validate(&ProgramSources { fragment, ..entity_sources() }) {
+            Err(ShaderError::Compile { stage: ShaderStage::Fragment, log }) => assert!(log.starts_with(expected), "{log:?} doesn't start with {expected:?}"),
+            other => panic!("fragment shader error expected for {fragment:?}, got {other:?}"),
+        }
+    }
+
+    // Comments, interpolation qualifiers and several names in one declaration are understood
+    let interface = parse_interface(ShaderStage::Fragment, "#version 330 core\n/* a;\n b */ flat in vec2 a, b[2]; // c;\nvoid main() {}\n").unwrap();
+    let names: Vec<(&str, bool, usize)> = interface.variables.iter().map(|variable| (variable.name.as_str(), variable.array, variable.line)).collect();
+    assert_eq!(names, [("a", false, 3), ("b", true, 3)]);
+}
+
+#[test]
+fn program_files_are_reloaded_on_change() {
+    let directory = std::env::temp_dir().join(format!("pixel_mobs_shaders_{}", std::process::id()));
+    std::fs::create_dir_all(&directory).unwrap();
+    let mut files = glsl::ProgramFiles::new(&directory, "entity");
+    let file_of = files.clone();
+    let write = |stage: ShaderStage, source: &str, age: u64| {
+        let path = file_of.path(stage);
+        std::fs::write(&path, source).unwrap();
+        // Explicit times, file system may not tell apart writes within same tick
+        let modified = std::time::SystemTime::now() - std::time::Duration::from_secs(age);
+        std::fs::File::options().write(true).open(&path).unwrap().set_modified(modified).unwrap();
+    };
+
+    assert!(matches!(files.load(), Err(ShaderError::MissingStage(ShaderStage::Vertex))));
+    write(ShaderStage::Vertex, ENTITY_VERT, 100);
+    write(ShaderStage::Fragment, ENTITY_FRAG, 100);
+    assert!(files.poll_changed());
+    assert!(!files.poll_changed());
+
+    // Geometry shader is optional
+    let loaded = files.load().unwrap();
+    assert_eq!(loaded.geometry, None);
+    assert_eq!(loaded.sources().fragment, ENTITY_FRAG);
+
+    write(ShaderStage::Geometry, ENTITY_GEOM, 50);
+    assert!(files.poll_changed());
+    assert!(validate(&files.load().unwrap().sources()).is_ok());
+
+    // Older file put back counts as change too
+    write(ShaderStage::Fragment, "broken", 200);
+    assert!(files.poll_changed());
+    assert!(validate(&files.load().unwrap().sources()).is_err());
+
+    std::fs::remove_file(files.path(ShaderStage::Geometry)).unwrap();
+    assert!(files.poll_changed());
+    std::fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn valid_glsl_is_accepted() {
+    let fragment = "#version 400 core
+precision lowp float;
+struct Light { vec3 color; float power; };
+uniform Light light;
+uniform isampler2D indices;
+uniform usampler2D flags;
+uniform sampler2DShadow shadow;
+uniform dmat4 transform;
+uniform float gain = 1.5;
+sample in vec3 gs_color;
+layout(location = 0) out vec4 out_color;
+
+float brightness(vec3 color) {
+    return dot(color, vec3(0.2126, 0.7152, 0.0722));
+}
+
+void main(void) {
+    out_color = vec4(light.color * gain * brightness(gs_color), 1);
+}
+";
+    let interface = parse_interface(ShaderStage::Fragment, fragment).unwrap();
+    assert_eq!(interface.version, "400 core");
+    assert_eq!(interface.variable(Storage::Uniform, "light").unwrap().type_name, "Light");
+    assert_eq!(interface.variable(Storage::Uniform, "transform").unwrap().type_name, "dmat4");
+    assert!(interface.variable(Storage::In, "gs_color").is_some());
+
+    // Tessellation control output, game has no such stage but parser knows it
+    let patch = "#version 400 core\npatch out vec4 corner;\nvoid main() {}\n";
+    assert!(parse_interface(ShaderStage::Vertex, patch).unwrap().variable(Storage::Out, "corner").is_some());
+}
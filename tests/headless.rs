@@ -0,0 +1,98 @@
+// Engine and timer must be usable without any window or GL context
+
+use pixel_mobs_gun_rs::{game, linmath, timer};
+
+type Vec2 = linmath::Vec2<f32>;
+
+#[test]
+fn engine_updates_without_display() {
+    let mut engine = game::Engine::new();
+    engine.enemies.push(game::Enemy::new(game::EnemyKind::Chaser, Vec2::new(0.5, 0.0)));
+
+    for _ in 0..10 {
+        engine.update(&game::PlayerInput::new());
+    }
+
+    // Enemy must move towards player
+    assert!(engine.enemies[0].position.x < 0.5);
+}
+
+#[test]
+fn timer_runs_on_manual_clock() {
+    let mut timer = timer::Timer::new(timer::ManualClock::new(1000));
+
+    timer.clock().advance(0.25);
+    timer.update();
+
+    assert!((timer.delta_time - 0.25).abs() < 1e-9);
+    assert!((timer.time - 0.25).abs() < 1e-9);
+}
+
+#[test]
+fn fixed_step_is_frame_rate_independent() {
+    let mut slow = game::Engine::new();
+    let mut fast = game::Engine::new();
+    let mut slow_step = game::Engine::fixed_step();
+    let mut fast_step = game::Engine::fixed_step();
+
+    let input = game::PlayerInput { move_axis: Vec2::new(1.0, 0.0), ..game::PlayerInput::new() };
+
+    // One second at 30 and 240 frames per second
+    for _ in 0..30 {
+        for _ in 0..slow_step.accumulate(1.0 / 30.0) {
+            slow.update(&input);
+        }
+    }
+    for _ in 0..240 {
+        for _ in 0..fast_step.accumulate(1.0 / 240.0) {
+            fast.update(&input);
+        }
+    }
+
+    assert!((slow.player.position.x - fast.player.position.x).abs() < game::PLAYER_SPEED * game::TICK_DURATION * 1.01);
+}
+
+#[test]
+fn fixed_step_caps_catch_up() {
+    let mut step = timer::FixedStep::new(100.0, 5);
+
+    // Long stall must not produce unbounded amount of ticks
+    assert_eq!(step.accumulate(10.0), 5);
+    assert_eq!(step.accumulate(0.0), 0);
+
+    assert_eq!(step.accumulate(0.015), 1);
+    assert!((step.alpha() - 0.5).abs() < 1e-4);
+}
+
+#[test]
+fn enemies_lose_health_before_dying() {
+    let mut engine = game::Engine::new();
+    engine.enemies.push(game::Enemy::new(game::EnemyKind::Brute, Vec2::new(0.5, 0.0)));
+
+    let shots = (game::EnemyKind::Brute.max_health() / game::BULLET_DAMAGE).ceil() as usize;
+    let mut hits = 0;
+    let mut kills = 0;
+
+    for _ in 0..shots {
+        engine.bullets.spawn(game::Bullet::new(Vec2::new(0.3, 0.0), Vec2::new(60.0, 0.0)));
+        engine.update(&game::PlayerInput::new());
+
+        for event in &engine.events {
+            match event {
+                game::GameEvent::EnemyHit { kind, damage, .. } => {
+                    assert_eq!(*kind, game::EnemyKind::Brute);
+                    assert_eq!(*damage, game::BULLET_DAMAGE);
+                    hits += 1;
+                }
+                game::GameEvent::EnemyKilled { .. } => kills += 1,
+                _ => {}
+            }
+        }
+
+        // Still alive until last shot
+        assert_eq!(engine.enemies.len(), if hits < shots { 1 } else { 0 });
+    }
+
+    assert_eq!(hits, shots);
+    assert_eq!(kills, 1);
+}
@@ -0,0 +1,185 @@
+// Input bindings and action state
+
+use pixel_mobs_gun_rs::game;
+use pixel_mobs_gun_rs::input::{self, Action, InputMap, InputMapError, InputState};
+use pixel_mobs_gun_rs::view::View;
+
+type Vec2 = game::Vec2;
+
+const PLAYER: Vec2 = Vec2 { x: 0.25, y: -0.25 };
+
+fn view() -> View {
+    View::new(800, 600)
+}
+
+#[test]
+fn default_bindings_are_valid() {
+    let map = InputMap::parse(input::DEFAULT_BINDINGS).unwrap();
+    let actions: Vec<Action> = map.bindings.iter().map(|binding| binding.action).collect();
+
+    for action in [Action::MoveX, Action::MoveY, Action::Fire, Action::Aim, Action::Reload, Action::Pause] {
+        assert!(actions.contains(&action), "{action:?} is not bound");
+    }
+    // Both WASD and arrows
+    assert_eq!(map.bindings_of("a").next().unwrap().value, -1.0);
+    assert_eq!(map.bindings_of("left").next().unwrap().value, -1.0);
+    assert_eq!(map.bindings_of("4").next().unwrap().action, Action::SwitchWeapon(3));
+}
+
+#[test]
+fn bindings_parsing() {
+    let map = InputMap::parse("
+        # comment
+        move_x  Right      # trailing comment
+        move_x  LeftShift  -0.5
+        fire    mouse_left
+        switch_weapon 9 2
+    ").unwrap();
+    assert_eq!(map.bindings.len(), 4);
+    assert_eq!(map.bindings[0].value, 1.0);
+    assert_eq!(map.bindings[1].input, "leftshift");
+    assert_eq!(map.bindings[1].value, -0.5);
+    assert_eq!(map.bindings[3].action, Action::SwitchWeapon(1));
+
+    for (source, line) in [("jump Space", 1), ("\nfire", 2), ("fire Space 1", 1), ("switch_weapon 1", 1), ("switch_weapon 1 9", 1), ("move_x D fast", 1), ("move_x Keypad 6", 1)] {
+        match InputMap::parse(source) {
+            Err(InputMapError::Parse { line: error_line, .. }) => assert_eq!(error_line, line, "{source:?}"),
+            other => panic!("parse error expected for {source:?}, got {other:?}"),
+        }
+    }
+}
+
+#[test]
+fn axes_combine_held_bindings() {
+    let mut state = InputState::new(InputMap::default_map());
+
+    state.press("D");
+    assert_eq!(state.player_input(PLAYER, &view()).move_axis, Vec2::new(1.0, 0.0));
+    // Opposite keys cancel out, same direction on two devices doesn't move faster
+    state.press("A");
+    assert_eq!(state.axis(Action::MoveX), 0.0);
+    state.release("A");
+    state.press("Right");
+    state.press("Up");
+    assert_eq!(state.player_input(PLAYER, &view()).move_axis, Vec2::new(1.0, 1.0));
+
+    state.release_all();
+    assert_eq!(state.player_input(PLAYER, &view()).move_axis, Vec2::new(0.0, 0.0));
+}
+
+#[test]
+fn presses_reach_single_tick() {
+    let mut state = InputState::new(InputMap::default_map());
+
+    // Tap between ticks still fires once
+    state.press("Space");
+    state.release("Space");
+    state.press("3");
+    state.press("R");
+    let tick = state.player_input(PLAYER, &view());
+    assert!(tick.fire);
+    assert!(tick.reload);
+    assert_eq!(tick.switch_weapon, Some(2));
+
+    state.end_tick();
+    let tick = state.player_input(PLAYER, &view());
+    assert!(!tick.fire && !tick.reload);
+    assert_eq!(tick.switch_weapon, None);
+
+    // Held trigger keeps firing, key repeat is no new press
+    state.press("mouse_left");
+    state.end_tick();
+    state.press("mouse_left");
+    assert!(state.player_input(PLAYER, &view()).fire);
+    assert!(!state.was_pressed(Action::Fire));
+}
+
+#[test]
+fn front_end_actions_are_consumed() {
+    let mut state = InputState::new(InputMap::default_map());
+
+    state.press("Escape");
+    state.release("Escape");
+    state.end_tick();
+    assert!(state.take_pressed(Action::Pause));
+    assert!(!state.take_pressed(Action::Pause));
+}
+
+#[test]
+fn rebinding() {
+    let mut map = InputMap::new();
+    map.bind(Action::Fire, "Left Ctrl", 1.0);
+    map.bind(Action::Aim, "mouse", 1.0);
+    let mut state = InputState::new(map);
+
+    state.press("Space");
+    assert!(!state.player_input(PLAYER, &view()).fire);
+    state.press("LeftCtrl");
+    assert!(state.player_input(PLAYER, &view()).fire);
+
+    // Pointer is given in pixels, aim is in world
+    state.point("mouse", Vec2::new(600.0, 450.0));
+    state.point("touch", Vec2::new(0.0, 0.0));
+    assert_eq!(state.player_input(PLAYER, &view()).aim, Vec2::new(2.0 / 3.0, -0.5));
+}
+
+#[test]
+fn deadzone_is_radial() {
+    assert_eq!(input::apply_deadzone(Vec2::new(0.1, 0.1), 0.2), Vec2::new(0.0, 0.0));
+
+    // Range past deadzone is rescaled, direction is kept
+    let stick = input::apply_deadzone(Vec2::new(0.6, 0.0), 0.2);
+    assert!((stick.x - 0.5).abs() < 1e-6 && stick.y == 0.0);
+    let stick = input::apply_deadzone(Vec2::new(0.0, -1.0), 0.2);
+    assert!((stick.y + 1.0).abs() < 1e-6);
+    let stick = input::apply_deadzone(Vec2::new(1.0, 1.0), 0.2);
+    assert!((stick.length() - 1.0).abs() < 1e-6 && (stick.x - stick.y).abs() < 1e-6);
+}
+
+#[test]
+fn gamepad_moves_aims_and_fires() {
+    let mut state = InputState::new(InputMap::default_map());
+
+    // Stick y points down
+    state.set_analog("pad_leftx", 0.5);
+    state.set_analog("pad_lefty", -0.25);
+    assert_eq!(state.player_input(PLAYER, &view()).move_axis, Vec2::new(0.5, 0.25));
+    // Keyboard adds to stick
+    state.press("D");
+    assert_eq!(state.player_input(PLAYER, &view()).move_axis, Vec2::new(1.0, 0.25));
+
+    // Twin-stick aim is relative to player and stays after stick is let go
+    state.set_analog("pad_rightx", 0.0);
+    state.set_analog("pad_righty", -0.8);
+    let expected = PLAYER + Vec2::new(0.0, input::STICK_AIM_DISTANCE);
+    assert!((state.player_input(PLAYER, &view()).aim - expected).length() < 1e-6);
+    state.set_analog("pad_righty", 0.0);
+    assert!((state.player_input(PLAYER, &view()).aim - expected).length() < 1e-6);
+    // Mouse takes over when it moves
+    state.point("mouse", Vec2::new(400.0, 300.0));
+    assert_eq!(state.player_input(PLAYER, &view()).aim, Vec2::new(0.0, 0.0));
+
+    // Trigger pulled past threshold is a press
+    state.set_analog("pad_righttrigger", 0.3);
+    assert!(!state.player_input(PLAYER, &view()).fire);
+    state.set_analog("pad_righttrigger", 0.9);
+    assert!(state.was_pressed(Action::Fire));
+    state.end_tick();
+    state.set_analog("pad_righttrigger", 1.0);
+    assert!(state.player_input(PLAYER, &view()).fire);
+    assert!(!state.was_pressed(Action::Fire));
+    state.set_analog("pad_righttrigger", 0.0);
+    assert!(!state.player_input(PLAYER, &view()).fire);
+
+    // Unplugged pad stops everything it did, keyboard keys stay held and aren't pressed again by repeats
+    state.press("Space");
+    state.set_analog("pad_leftx", 0.5);
+    state.press("pad_dpup");
+    state.end_tick();
+    state.release_device("pad_");
+    assert_eq!(state.player_input(PLAYER, &view()).move_axis, Vec2::new(1.0, 0.0));
+    assert!(!state.is_held(Action::SwitchWeapon(1)));
+    state.press("Space");
+    assert!(!state.was_pressed(Action::Fire));
+    assert!(state.player_input(PLAYER, &view()).fire);
+}
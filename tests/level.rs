@@ -0,0 +1,120 @@
+// Tile map levels
+
+use pixel_mobs_gun_rs::game::{self, EnemyKind};
+use pixel_mobs_gun_rs::level::{self, Level, LevelError, Tile};
+use pixel_mobs_gun_rs::pickup::PickupKind;
+use pixel_mobs_gun_rs::spawner::{self, WaveTable};
+
+type Vec2 = game::Vec2;
+
+const SMALL_LEVEL: &str = "
+; test level
+tile_size 0.5
+######
+#P..E#
+#.##.#
+#H...#
+######
+";
+
+#[test]
+fn level_parses_tiles_and_markers() {
+    let level = Level::parse(SMALL_LEVEL).unwrap();
+
+    assert_eq!((level.width, level.height), (6, 5));
+    assert_eq!(level.tile_size, 0.5);
+    assert_eq!(level.tile(0, 0), Some(Tile::Wall));
+    assert_eq!(level.tile(2, 2), Some(Tile::Wall));
+    assert_eq!(level.tile(1, 1), Some(Tile::Floor));
+    assert_eq!(level.tile(6, 0), None);
+
+    // Map is centered, top row first
+    assert_eq!(level.bounds().min, Vec2::new(-1.5, -1.25));
+    assert_eq!(level.player_start, Vec2::new(-0.75, 0.5));
+    assert_eq!(level.spawn_points, vec![Vec2::new(0.75, 0.5)]);
+    assert_eq!(level.pickups, vec![(PickupKind::Health, Vec2::new(-0.75, -0.5))]);
+}
+
+#[test]
+fn broken_levels_are_reported() {
+    let error_line = |source: &str| match Level::parse(source) {
+        Err(LevelError::Parse { line, .. }) => line,
+        other => panic!("parse error expected, got {other:?}"),
+    };
+
+    assert_eq!(error_line("###\n#P#\n##"), 3);
+    assert_eq!(error_line("###\n#X#\n###"), 2);
+    assert_eq!(error_line("###\n#.#\n###"), 1);
+    assert_eq!(error_line("#PP#"), 1);
+    assert_eq!(error_line("tile_size -1\n#P#"), 1);
+    assert_eq!(error_line("#P#\ntile_size 1"), 2);
+    assert_eq!(error_line("; nothing"), 1);
+}
+
+#[test]
+fn built_in_level_is_valid() {
+    let level = Level::default_level();
+    assert!(!level.spawn_points.is_empty());
+    assert!(!level.is_wall(level.player_start));
+    assert_eq!(Level::parse(level::DEFAULT_LEVEL).unwrap(), level);
+    assert_eq!(Level::parse(&level.to_source()).unwrap(), level);
+}
+
+#[test]
+fn walls_are_merged_into_rectangles() {
+    let level = Level::parse(SMALL_LEVEL).unwrap();
+    let rects = level.wall_rects();
+
+    // Every wall tile is covered, no floor tile is
+    for row in 0..level.height {
+        for column in 0..level.width {
+            let center = level.tile_center(column, row);
+            let covered = rects.iter().any(|rect| rect.contains(center));
+            assert_eq!(covered, level.tile(column, row) == Some(Tile::Wall));
+        }
+    }
+    // Side walls are merged vertically
+    assert!(rects.len() < 8);
+}
+
+#[test]
+fn tile_queries() {
+    let level = Level::parse(SMALL_LEVEL).unwrap();
+
+    assert_eq!(level.tile_coords(Vec2::new(-0.75, 0.5)), Some((1, 1)));
+    assert_eq!(level.tile_coords(Vec2::new(10.0, 0.0)), None);
+    assert!(level.is_wall(Vec2::new(10.0, 0.0)));
+    assert!(level.is_wall(Vec2::new(-0.25, 0.0)));
+
+    // Along row 1 and column 1 is clear, through central wall block is not
+    assert!(level.line_of_sight(Vec2::new(-0.75, 0.5), Vec2::new(0.75, 0.5)));
+    assert!(level.line_of_sight(Vec2::new(-0.75, 0.5), Vec2::new(-0.75, -0.5)));
+    assert!(!level.line_of_sight(Vec2::new(-0.75, 0.5), Vec2::new(0.75, -0.5)));
+    assert!(!level.line_of_sight(Vec2::new(0.0, 0.5), Vec2::new(0.0, -0.5)));
+}
+
+#[test]
+fn engine_plays_loaded_level() {
+    let mut engine = game::Engine::new_game_with_waves(1, WaveTable::parse("1 chaser 6 0.001 edges").unwrap());
+    let level = Level::parse(SMALL_LEVEL).unwrap();
+    let spawn_point = level.spawn_points[0];
+    engine.load_level(level);
+
+    assert_eq!(engine.player.position, Vec2::new(-0.75, 0.5));
+    assert_eq!(engine.pickups.len(), 1);
+    assert!(engine.is_wall(Vec2::new(-0.25, 0.0)));
+    assert!(!engine.line_of_sight(engine.player.position, Vec2::new(0.75, -0.5)));
+
+    // Enemies come out of spawn point, placed pickups don't expire
+    engine.player.health = 1.0e9;
+    for _ in 0..(spawner::WAVE_DELAY / game::TICK_DURATION) as usize + 2 {
+        engine.update(&game::PlayerInput::new());
+    }
+    assert_eq!(engine.enemies.len(), 6);
+    for enemy in &engine.enemies {
+        assert_eq!(enemy.kind, EnemyKind::Chaser);
+        assert!((enemy.position - spawn_point).length() < 0.3);
+        assert!(engine.arena.is_free(enemy.position, enemy.kind.radius() * 0.99));
+    }
+    assert_eq!(engine.pickups.len(), 1);
+}
@@ -0,0 +1,117 @@
+// Navigation grid and flow field pathfinding
+
+use pixel_mobs_gun_rs::arena::Arena;
+use pixel_mobs_gun_rs::collision::Aabb;
+use pixel_mobs_gun_rs::game::{self, EnemyKind};
+use pixel_mobs_gun_rs::level::Level;
+use pixel_mobs_gun_rs::nav::{FlowField, NavGrid};
+
+type Vec2 = game::Vec2;
+
+// U-shaped wall opening to the right, player sits inside it
+fn cup_arena() -> Arena {
+    Arena::with_obstacles(
+        Aabb::from_center(Vec2::new(0.0, 0.0), Vec2::new(1.5, 1.0)),
+        vec![
+            Aabb::new(Vec2::new(-0.5, -0.5), Vec2::new(-0.4, 0.5)),
+            Aabb::new(Vec2::new(-0.5, 0.4), Vec2::new(0.3, 0.5)),
+            Aabb::new(Vec2::new(-0.5, -0.5), Vec2::new(0.3, -0.4)),
+        ],
+    )
+}
+
+#[test]
+fn cells_near_walls_are_blocked() {
+    let arena = cup_arena();
+    let grid = NavGrid::from_arena(&arena, 0.05, 0.03);
+
+    assert_eq!((grid.width, grid.height), (60, 40));
+    let blocked = |position: Vec2| grid.is_blocked(grid.cell_index(position).unwrap());
+
+    assert!(blocked(Vec2::new(-0.45, 0.0)));
+    assert!(blocked(Vec2::new(-0.375, 0.0)));
+    assert!(blocked(Vec2::new(1.49, 0.0)));
+    assert!(!blocked(Vec2::new(-0.3, 0.0)));
+    assert!(!blocked(Vec2::new(-0.7, 0.0)));
+    assert_eq!(grid.cell_index(Vec2::new(2.0, 0.0)), None);
+
+    let center = grid.cell_center(grid.cell_index(Vec2::new(0.01, 0.01)).unwrap());
+    assert!((center - Vec2::new(0.025, 0.025)).length() < 1e-5);
+}
+
+#[test]
+fn flow_field_leads_around_walls() {
+    let arena = cup_arena();
+    let grid = NavGrid::from_arena(&arena, 0.05, 0.03);
+    let mut field = FlowField::new();
+    field.update(&grid, Vec2::new(-0.2, 0.0));
+
+    // Behind the cup, path goes around it, longer than straight line
+    let start = grid.cell_index(Vec2::new(-0.8, 0.0)).unwrap();
+    let cost = field.cost(start).unwrap();
+    assert!(cost > 10 * 20);
+
+    let mut cell = start;
+    let mut steps = 0;
+    while let Some(next) = field.next(cell) {
+        assert!(!grid.is_blocked(next) || Some(next) == field.target());
+        cell = next;
+        steps += 1;
+    }
+    assert_eq!(Some(cell), field.target());
+    assert!(steps > 20);
+
+    // Initial direction is up or down around the cup, not into its back wall
+    let direction = field.direction(&grid, Vec2::new(-0.8, 0.0)).unwrap();
+    assert!(direction.y.abs() > 0.5);
+}
+
+#[test]
+fn flow_field_is_deterministic() {
+    let level = Level::default_level();
+    let grid = NavGrid::from_arena(&level.arena(), game::NAV_CELL_SIZE, game::NAV_CLEARANCE);
+    let mut lhs = FlowField::new();
+    let mut rhs = FlowField::new();
+    lhs.update(&grid, level.player_start);
+    rhs.update(&grid, level.player_start);
+
+    for cell in 0..grid.cell_count() {
+        assert_eq!(lhs.next(cell), rhs.next(cell));
+        assert_eq!(lhs.cost(cell), rhs.cost(cell));
+    }
+    // Every spawn point reaches player
+    for spawn_point in &level.spawn_points {
+        assert!(lhs.cost(grid.cell_index(*spawn_point).unwrap()).is_some());
+    }
+}
+
+#[test]
+fn enemy_reaches_player_inside_cup() {
+    let mut engine = game::Engine::new();
+    engine.arena = cup_arena();
+    engine.rebuild_nav();
+    engine.player.health = 1000.0;
+    engine.player.position = Vec2::new(-0.2, 0.0);
+    engine.enemies.push(game::Enemy::new(EnemyKind::Chaser, Vec2::new(-0.9, 0.0)));
+
+    for _ in 0..(6.0 / game::TICK_DURATION) as usize {
+        engine.update(&game::PlayerInput::new());
+    }
+    // Got into the cup and hit player
+    let enemy = &engine.enemies[0];
+    assert!(enemy.position.x > -0.4 && enemy.position.y.abs() < 0.4);
+    assert!(engine.player.health < 1000.0);
+
+    // Without navigation it stays stuck behind the cup
+    let mut engine = game::Engine::new();
+    engine.arena = cup_arena();
+    engine.player.health = 1000.0;
+    engine.player.position = Vec2::new(-0.2, 0.0);
+    engine.enemies.push(game::Enemy::new(EnemyKind::Chaser, Vec2::new(-0.9, 0.0)));
+
+    for _ in 0..(6.0 / game::TICK_DURATION) as usize {
+        engine.update(&game::PlayerInput::new());
+    }
+    assert!(engine.enemies[0].position.x < -0.5);
+    assert_eq!(engine.player.health, 1000.0);
+}
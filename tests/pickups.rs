@@ -0,0 +1,129 @@
+// Pickup drops, collection and timed effects
+
+mod common;
+
+use pixel_mobs_gun_rs::enemy;
+use pixel_mobs_gun_rs::game::{self, EnemyKind, GameEvent, WeaponKind};
+use pixel_mobs_gun_rs::pickup::{self, Pickup, PickupKind};
+use pixel_mobs_gun_rs::rng::Rng;
+
+type Vec2 = game::Vec2;
+
+fn collect(engine: &mut game::Engine, kind: PickupKind) {
+    engine.pickups.push(Pickup::new(kind, engine.player.position));
+    engine.update(&game::PlayerInput::new());
+
+    assert!(engine.pickups.is_empty());
+    assert!(engine.events.contains(&GameEvent::PickupCollected { kind, position: engine.player.position }));
+}
+
+#[test]
+fn drop_tables_are_valid() {
+    for kind in pickup::ALL_PICKUP_KINDS {
+        let name = format!("{kind:?}").to_lowercase().replace("boost", "_boost");
+        assert_eq!(PickupKind::from_name(&name), Some(kind));
+    }
+    for kind in enemy::ALL_ENEMY_KINDS {
+        let total: f32 = pickup::drop_table(kind).iter().map(|drop| drop.chance).sum();
+        assert!((0.0..=1.0).contains(&total));
+    }
+}
+
+#[test]
+fn drops_follow_table_chances() {
+    let mut rng = Rng::new(5);
+    let rolls = 20000;
+    let mut health = 0;
+    let mut boosts = 0;
+
+    for _ in 0..rolls {
+        match Pickup::roll(EnemyKind::Brute, Vec2::new(0.0, 0.0), &mut rng).map(|pickup| pickup.kind) {
+            Some(PickupKind::Health) => health += 1,
+            Some(PickupKind::WeaponBoost) => boosts += 1,
+            Some(kind) => panic!("{kind:?} is not in brute drop table"),
+            None => {}
+        }
+    }
+
+    assert!((health as f32 / rolls as f32 - 0.25).abs() < 0.02);
+    assert!((boosts as f32 / rolls as f32 - 0.15).abs() < 0.02);
+    assert!(Pickup::roll(EnemyKind::Splitling, Vec2::new(0.0, 0.0), &mut rng).is_none());
+}
+
+#[test]
+fn killed_enemies_drop_pickups() {
+    let mut engine = game::Engine::with_seed(3);
+    engine.player.health = 1.0e9;
+
+    for _ in 0..200 {
+        engine.enemies.push(game::Enemy::new(EnemyKind::Brute, Vec2::new(1.0, 0.5)));
+        engine.enemies[0].health = 0.0;
+        engine.update(&game::PlayerInput::new());
+    }
+
+    // Expected count is 80, each pickup lies where its enemy died
+    assert!(engine.pickups.len() > 40);
+    for pickup in &engine.pickups {
+        assert!((pickup.position - Vec2::new(1.0, 0.5)).length() < 0.01);
+    }
+}
+
+#[test]
+fn health_pickup_heals_up_to_max() {
+    let mut engine = game::Engine::new();
+    engine.player.health = 50.0;
+    collect(&mut engine, PickupKind::Health);
+    assert_eq!(engine.player.health, 50.0 + pickup::HEALTH_PICKUP_AMOUNT);
+
+    engine.player.health = game::PLAYER_MAX_HEALTH - 1.0;
+    collect(&mut engine, PickupKind::Health);
+    assert_eq!(engine.player.health, game::PLAYER_MAX_HEALTH);
+}
+
+#[test]
+fn ammo_pickup_refills_magazines() {
+    let mut engine = game::Engine::new();
+    for weapon in &mut engine.player.weapons {
+        weapon.ammo = 0;
+        weapon.reload();
+    }
+    collect(&mut engine, PickupKind::Ammo);
+
+    for weapon in &engine.player.weapons {
+        assert_eq!(weapon.ammo, weapon.stats().magazine);
+        assert!(!weapon.is_reloading());
+    }
+}
+
+#[test]
+fn timed_boosts_expire() {
+    let mut engine = game::Engine::new();
+    collect(&mut engine, PickupKind::SpeedBoost);
+    collect(&mut engine, PickupKind::WeaponBoost);
+
+    let start = engine.player.position;
+    engine.update(&game::PlayerInput { move_axis: Vec2::new(1.0, 0.0), aim: Vec2::new(1.0, 0.0), fire: true, ..game::PlayerInput::new() });
+    let speed = (engine.player.position - start).length() / game::TICK_DURATION;
+    assert!((speed - game::PLAYER_SPEED * pickup::SPEED_BOOST_SCALE).abs() < 1e-3);
+
+    let bullet = engine.bullets.as_slice()[0];
+    assert_eq!(engine.player.weapon().kind, WeaponKind::Pistol);
+    assert_eq!(bullet.damage, WeaponKind::Pistol.stats().damage * pickup::WEAPON_BOOST_DAMAGE_SCALE);
+    assert_eq!(bullet.pierce, pickup::WEAPON_BOOST_EXTRA_PIERCE);
+
+    common::run_for(&mut engine, &game::PlayerInput::new(), pickup::WEAPON_BOOST_DURATION.max(pickup::SPEED_BOOST_DURATION) + 0.1);
+    assert_eq!(engine.player.speed_boost_time, 0.0);
+    assert_eq!(engine.player.weapon_boost_time, 0.0);
+    assert_eq!(engine.player.speed(), game::PLAYER_SPEED);
+}
+
+#[test]
+fn uncollected_pickups_expire() {
+    let mut engine = game::Engine::new();
+    engine.pickups.push(Pickup::new(PickupKind::Health, Vec2::new(1.0, 0.5)));
+
+    common::run_for(&mut engine, &game::PlayerInput::new(), pickup::PICKUP_LIFETIME - 0.1);
+    assert_eq!(engine.pickups.len(), 1);
+    common::run_for(&mut engine, &game::PlayerInput::new(), 0.2);
+    assert!(engine.pickups.is_empty());
+}
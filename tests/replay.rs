@@ -0,0 +1,156 @@
+// Replays must reproduce exactly the same engine state
+
+use pixel_mobs_gun_rs::{game, level, replay, rng, spawner};
+
+type Vec2 = game::Vec2;
+
+fn random_replay(seed: u64, ticks: usize) -> replay::Replay {
+    let mut rng = rng::Rng::new(seed ^ 0xABCD);
+    let mut replay = replay::Replay::new(seed, spawner::WaveTable::default_waves(), None);
+    let mut input = game::PlayerInput::new();
+
+    for _ in 0..ticks {
+        // Keep inputs for a while, like real player does
+        if rng.below(16) == 0 {
+            input.move_axis = Vec2::new(rng.below(3) as f32 - 1.0, rng.below(3) as f32 - 1.0);
+            input.aim = Vec2::new(rng.range_f32(-1.0, 1.0), rng.range_f32(-1.0, 1.0));
+        }
+        input.fire = rng.below(20) == 0;
+        input.reload = rng.below(300) == 0;
+        input.switch_weapon = if rng.below(200) == 0 { Some(rng.below(4) as usize) } else { None };
+        replay.record(&input);
+    }
+
+    replay
+}
+
+fn assert_same_state(lhs: &game::Engine, rhs: &game::Engine) {
+    assert_eq!(lhs.tick(), rhs.tick());
+    assert_eq!(lhs.player.position, rhs.player.position);
+    assert_eq!(lhs.player.health, rhs.player.health);
+    assert_eq!(lhs.enemies.len(), rhs.enemies.len());
+    assert_eq!(lhs.bullets.len(), rhs.bullets.len());
+
+    for (lhs, rhs) in lhs.enemies.iter().zip(&rhs.enemies) {
+        assert_eq!(lhs.position, rhs.position);
+    }
+    for (lhs, rhs) in lhs.bullets.iter().zip(&rhs.bullets) {
+        assert_eq!(lhs.position, rhs.position);
+    }
+}
+
+#[test]
+fn playback_reproduces_recorded_game() {
+    let recorded = random_replay(42, 2000);
+
+    let mut live = game::Engine::new_game(42);
+    for input in &recorded.inputs {
+        live.update(input);
+    }
+
+    assert_same_state(&live, &recorded.play());
+}
+
+#[test]
+fn replay_survives_serialization() {
+    let recorded = random_replay(7, 1000);
+
+    let mut bytes = Vec::<u8>::new();
+    recorded.write_to(&mut bytes).unwrap();
+    let loaded = replay::Replay::read_from(&mut bytes.as_slice()).unwrap();
+
+    assert_eq!(recorded, loaded);
+    assert_same_state(&recorded.play(), &loaded.play());
+
+    // Held inputs must be run-length encoded
+    assert!(bytes.len() < recorded.inputs.len() * 10);
+}
+
+#[test]
+fn replay_rejects_foreign_data() {
+    assert!(matches!(
+        replay::Replay::read_from(&mut &b"NOPE\x01\x00"[..]),
+        Err(replay::ReplayError::BadMagic)
+    ));
+    assert!(matches!(
+        replay::Replay::read_from(&mut &b"PMGR\x01"[..]),
+        Err(replay::ReplayError::Io(_))
+    ));
+}
+
+// Wave table text follows magic, version, seed and its length
+const WAVES_OFFSET: usize = 4 + 2 + 8 + 4;
+
+// Replay file up to its run count
+fn replay_header() -> Vec<u8> {
+    let mut bytes = Vec::<u8>::new();
+    random_replay(7, 0).write_to(&mut bytes).unwrap();
+    bytes.truncate(bytes.len() - 4);
+    bytes
+}
+
+#[test]
+fn replay_length_is_limited() {
+    // One run claiming nearly 2^32 ticks
+    let mut bytes = replay_header();
+    bytes.extend(1u32.to_le_bytes());
+    bytes.extend(u32::MAX.to_le_bytes());
+    bytes.extend([0u8; 18]);
+    assert!(matches!(replay::Replay::read_from(&mut bytes.as_slice()), Err(replay::ReplayError::TooLong)));
+
+    // Many runs adding up past the limit
+    let mut bytes = replay_header();
+    bytes.extend(u32::MAX.to_le_bytes());
+    for _ in 0..replay::MAX_REPLAY_TICKS / 1000 + 1 {
+        bytes.extend(1000u32.to_le_bytes());
+        bytes.extend([0u8; 18]);
+    }
+    assert!(matches!(replay::Replay::read_from(&mut bytes.as_slice()), Err(replay::ReplayError::TooLong)));
+}
+
+#[test]
+fn replay_plays_waves_it_was_recorded_with() {
+    let waves = spawner::WaveTable::parse("1 brute 2 0.25 ring\n1 shooter 1 0.5 corners\n2 dasher 3 1e-3 side\n").unwrap();
+    let mut recorded = random_replay(11, 600);
+    recorded.waves = waves.clone();
+
+    let mut bytes = Vec::<u8>::new();
+    recorded.write_to(&mut bytes).unwrap();
+    let loaded = replay::Replay::read_from(&mut bytes.as_slice()).unwrap();
+    assert_eq!(loaded.waves, waves);
+
+    let mut live = game::Engine::new_game_with_waves(11, waves);
+    for input in &recorded.inputs {
+        live.update(input);
+    }
+    assert_same_state(&live, &loaded.play());
+    assert!(live.enemies.iter().any(|enemy| enemy.kind == game::EnemyKind::Brute));
+
+    // Wave table is validated like one read from file
+    bytes[WAVES_OFFSET..WAVES_OFFSET + 5].copy_from_slice(b"9 bat");
+    assert!(matches!(replay::Replay::read_from(&mut bytes.as_slice()), Err(replay::ReplayError::Waves(_))));
+}
+
+#[test]
+fn replay_plays_level_it_was_recorded_on() {
+    let level = level::Level::parse("tile_size 0.2\n#######\n#E.A.E#\n#..#..#\n#.P.H.#\n#######\n").unwrap();
+    let mut recorded = random_replay(13, 600);
+    recorded.level = Some(level.clone());
+
+    let mut bytes = Vec::<u8>::new();
+    recorded.write_to(&mut bytes).unwrap();
+    let loaded = replay::Replay::read_from(&mut bytes.as_slice()).unwrap();
+    assert_eq!(loaded.level.as_ref(), Some(&level));
+
+    let mut live = game::Engine::new_game(13);
+    live.load_level(level);
+    for input in &recorded.inputs {
+        live.update(input);
+    }
+    assert_same_state(&live, &loaded.play());
+
+    // Level is validated like one read from file
+    let map = bytes.windows(7).position(|window| window == b"#######").unwrap();
+    bytes[map..map + 7].copy_from_slice(b"###?###");
+    assert!(matches!(replay::Replay::read_from(&mut bytes.as_slice()), Err(replay::ReplayError::Level(_))));
+}
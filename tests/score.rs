@@ -0,0 +1,127 @@
+// Score, combo multiplier and high score table
+
+mod common;
+
+use pixel_mobs_gun_rs::game::{self, EnemyKind, GameState};
+use pixel_mobs_gun_rs::score::{self, HighScore, HighScoreTable, Score};
+
+type Vec2 = game::Vec2;
+
+fn entry(name: &str, points: u64) -> HighScore {
+    HighScore { name: name.to_string(), points, date: 1_700_000_000, seed: points * 7 }
+}
+
+fn temporary_path(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("pmg_{}_{name}", std::process::id()))
+}
+
+#[test]
+fn combo_raises_and_decays_multiplier() {
+    let mut score = Score::new();
+    let points = score::kill_points(EnemyKind::Chaser);
+
+    for _ in 0..score::COMBO_KILLS_PER_LEVEL {
+        score.record_kill(EnemyKind::Chaser);
+    }
+    assert_eq!(score.multiplier, 2);
+    assert_eq!(score.points, points * score::COMBO_KILLS_PER_LEVEL as u64);
+
+    score.record_kill(EnemyKind::Chaser);
+    assert_eq!(score.points, points * (score::COMBO_KILLS_PER_LEVEL as u64 + 2));
+
+    // Kill streaks can't raise multiplier without bound
+    for _ in 0..1000 {
+        score.record_kill(EnemyKind::Chaser);
+    }
+    assert_eq!(score.multiplier, score::MAX_MULTIPLIER);
+
+    // One level is lost per window without kills
+    score.update(score::COMBO_WINDOW + 0.01);
+    assert_eq!(score.multiplier, score::MAX_MULTIPLIER - 1);
+    for _ in 0..score::MAX_MULTIPLIER {
+        score.update(score::COMBO_WINDOW + 0.01);
+    }
+    assert_eq!(score.multiplier, 1);
+}
+
+#[test]
+fn engine_tracks_kills_and_accuracy() {
+    let mut engine = common::engine_with(EnemyKind::Chaser, Vec2::new(0.3, 0.0));
+    engine.enemies[0].health = 1.0;
+
+    let aim = |aim: Vec2, fire: bool| game::PlayerInput { aim, fire, ..game::PlayerInput::new() };
+    engine.update(&aim(Vec2::new(1.0, 0.0), true));
+    engine.update(&aim(Vec2::new(-1.0, 0.0), false));
+    for _ in 0..40 {
+        engine.update(&aim(Vec2::new(-1.0, 0.0), true));
+        engine.update(&aim(Vec2::new(-1.0, 0.0), false));
+    }
+
+    let summary = engine.summary();
+    assert_eq!(summary.kills, 1);
+    assert_eq!(summary.points, score::kill_points(EnemyKind::Chaser));
+    assert!(engine.score.bullets_fired > 1);
+    assert_eq!(summary.accuracy, 1.0 / engine.score.bullets_fired as f32);
+    assert!((summary.time_survived - engine.tick() as f32 * game::TICK_DURATION).abs() < 1e-3);
+}
+
+#[test]
+fn survival_time_stops_with_player() {
+    let mut engine = game::Engine::new();
+    engine.player.health = 1.0;
+    engine.enemies.push(game::Enemy::new(EnemyKind::Brute, Vec2::new(0.2, 0.0)));
+
+    while engine.state() != GameState::GameOver {
+        engine.update(&game::PlayerInput::new());
+    }
+    let death_time = engine.summary().time_survived;
+    assert!(death_time < engine.tick() as f32 * game::TICK_DURATION - game::DEATH_DURATION + 0.1);
+}
+
+#[test]
+fn high_score_table_keeps_best_runs() {
+    let mut table = HighScoreTable::new();
+    for points in 0..score::MAX_HIGH_SCORES as u64 {
+        assert!(table.insert(entry("old", points * 100 + 100)).is_some());
+    }
+
+    assert!(!table.qualifies(100));
+    assert_eq!(table.insert(entry("low", 50)), None);
+    assert_eq!(table.insert(entry("best", 5000)), Some(0));
+    assert_eq!(table.insert(entry("tie", 500)), Some(7));
+
+    assert_eq!(table.entries.len(), score::MAX_HIGH_SCORES);
+    assert!(table.entries.windows(2).all(|pair| pair[0].points >= pair[1].points));
+    assert_eq!(table.entries[6].name, "old");
+}
+
+#[test]
+fn high_scores_survive_save_and_load() {
+    let path = temporary_path("high_scores.txt");
+    let mut table = HighScoreTable::new();
+    table.insert(entry("first player", 300));
+    table.insert(entry("second\nplayer", 200));
+
+    table.save(&path).unwrap();
+    let loaded = HighScoreTable::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(loaded.entries.len(), 2);
+    assert_eq!(loaded.entries[0], table.entries[0]);
+    assert_eq!(loaded.entries[1].name, "second player");
+
+    // Missing table is empty, broken one is an error
+    assert_eq!(HighScoreTable::load(&path).unwrap(), HighScoreTable::new());
+    assert!(matches!(HighScoreTable::parse("garbage"), Err(score::HighScoreError::Parse { line: 1, .. })));
+    let mut bytes = Vec::new();
+    table.write_to(&mut bytes).unwrap();
+    let corrupted = String::from_utf8(bytes).unwrap().replace("300", "3x0");
+    assert!(matches!(HighScoreTable::parse(&corrupted), Err(score::HighScoreError::Parse { line: 2, .. })));
+}
+
+#[test]
+fn dates_are_formatted_in_utc() {
+    assert_eq!(score::format_date(0), "1970-01-01");
+    assert_eq!(score::format_date(951_782_400), "2000-02-29");
+    assert_eq!(score::format_date(1_700_000_000), "2023-11-14");
+}
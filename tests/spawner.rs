@@ -0,0 +1,73 @@
+// Wave table parsing and wave progression
+
+use pixel_mobs_gun_rs::game::{self, GameEvent};
+use pixel_mobs_gun_rs::spawner::{self, SpawnPattern, WaveTable, WaveTableError};
+
+#[test]
+fn default_table_is_valid() {
+    let table = WaveTable::parse(spawner::DEFAULT_WAVES).unwrap();
+
+    assert!(!table.waves.is_empty());
+    assert!(table.waves.windows(2).all(|pair| pair[0].number < pair[1].number));
+    assert_eq!(WaveTable::parse(&table.to_source()).unwrap(), table);
+}
+
+#[test]
+fn table_parsing() {
+    let table = WaveTable::parse("
+        # comment
+        2 brute  1 0.0 corners # trailing comment
+        1 chaser 3 0.5 ring
+        2 chaser 2 1.0 side
+    ").unwrap();
+
+    assert_eq!(table.waves.len(), 2);
+    assert_eq!(table.waves[0].number, 1);
+    assert_eq!(table.waves[0].groups[0].count, 3);
+    assert_eq!(table.waves[0].groups[0].pattern, SpawnPattern::Ring);
+    assert_eq!(table.waves[1].groups.len(), 2);
+    assert_eq!(table.waves[1].groups[0].kind, game::EnemyKind::Brute);
+
+    for (source, line) in [("1 chaser 3 0.5", 1), ("\n1 dragon 3 0.5 ring", 2), ("1 chaser -3 0.5 ring", 1), ("1 chaser 3 -1 ring", 1), ("1 chaser 3 inf ring", 1), ("1 chaser 3 NaN ring", 1), ("1 chaser 1001 0.5 ring", 1), ("1 chaser 4294967295 0.5 ring", 1), ("1 chaser 2 0 ring", 1), ("1 chaser 3 0.5 spiral", 1)] {
+        match WaveTable::parse(source) {
+            Err(WaveTableError::Parse { line: error_line, .. }) => assert_eq!(error_line, line, "{source}"),
+            other => panic!("parse error expected for '{source}', got {other:?}"),
+        }
+    }
+}
+
+#[test]
+fn waves_spawn_on_edges_and_escalate() {
+    let table = WaveTable::parse("1 chaser 2 0.1 edges").unwrap();
+    let mut engine = game::Engine::new_game_with_waves(3, table);
+    let mut counts = Vec::new();
+    let mut wave_events = Vec::<GameEvent>::new();
+
+    // Player must survive the whole test
+    engine.player.health = 1.0e9;
+
+    for _ in 0..3 {
+        let mut spawned = 0;
+
+        // Let wave start and spawn everything
+        for _ in 0..((spawner::WAVE_DELAY + 1.0) / game::TICK_DURATION) as usize {
+            let before = engine.enemies.len();
+            engine.update(&game::PlayerInput::new());
+            spawned += engine.enemies.len() - before;
+            wave_events.extend(engine.events.iter().filter(|event| matches!(event, GameEvent::WaveStarted { .. })));
+
+            // Spawned enemies may already make one step inside
+            for enemy in &engine.enemies[before..] {
+                let bounds = engine.arena.bounds.expanded(-enemy.kind.radius() - 0.02);
+                assert!(!bounds.contains(enemy.position), "enemies must spawn at arena edge");
+            }
+        }
+
+        counts.push(spawned);
+        engine.enemies.clear();
+    }
+
+    assert_eq!(engine.spawner.wave(), 3);
+    assert_eq!(counts, [2, 3, 4]);
+    assert_eq!(wave_events, [GameEvent::WaveStarted { wave: 1 }, GameEvent::WaveStarted { wave: 2 }, GameEvent::WaveStarted { wave: 3 }]);
+}
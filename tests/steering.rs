@@ -0,0 +1,116 @@
+// Enemy crowd steering
+
+mod common;
+
+use pixel_mobs_gun_rs::collision::Aabb;
+use pixel_mobs_gun_rs::game::{self, EnemyKind};
+use pixel_mobs_gun_rs::steering::SteeringConfig;
+
+type Vec2 = game::Vec2;
+
+fn closest_pair_distance(engine: &game::Engine) -> f32 {
+    let mut closest = f32::MAX;
+    for (index, enemy) in engine.enemies.iter().enumerate() {
+        for other in &engine.enemies[index + 1..] {
+            closest = closest.min((enemy.position - other.position).length());
+        }
+    }
+    closest
+}
+
+fn converging_chasers(steering: SteeringConfig) -> game::Engine {
+    let mut engine = common::sturdy_engine();
+    engine.steering = steering;
+    engine.player.position = Vec2::new(0.0, 0.0);
+    for position in [Vec2::new(-1.0, -0.8), Vec2::new(1.0, -0.8), Vec2::new(1.0, 0.8), Vec2::new(-1.0, 0.8)] {
+        engine.enemies.push(game::Enemy::new(EnemyKind::Chaser, position));
+    }
+    engine
+}
+
+#[test]
+fn converging_chasers_stay_apart() {
+    let mut engine = converging_chasers(SteeringConfig::default());
+    common::run_for(&mut engine, &game::PlayerInput::new(), 5.0);
+
+    // Close to player, but not on top of each other
+    let diameter = EnemyKind::Chaser.radius() * 2.0;
+    assert!(closest_pair_distance(&engine) > diameter * 0.8);
+    for enemy in &engine.enemies {
+        assert!(enemy.position.length() < 0.3);
+    }
+}
+
+#[test]
+fn without_separation_chasers_collapse() {
+    let steering = SteeringConfig { separation_weight: 0.0, ..SteeringConfig::default() };
+    let mut engine = converging_chasers(steering);
+    common::run_for(&mut engine, &game::PlayerInput::new(), 5.0);
+
+    assert!(closest_pair_distance(&engine) < EnemyKind::Chaser.radius());
+}
+
+#[test]
+fn coincident_enemies_are_pushed_apart() {
+    let mut engine = common::sturdy_engine();
+    engine.player.position = Vec2::new(1.0, 0.0);
+    for _ in 0..2 {
+        engine.enemies.push(game::Enemy::new(EnemyKind::Brute, Vec2::new(-1.0, 0.0)));
+    }
+    common::run_for(&mut engine, &game::PlayerInput::new(), 1.0);
+
+    assert!(closest_pair_distance(&engine) > 0.05);
+}
+
+#[test]
+fn obstacle_pushes_steering_away_before_contact() {
+    let steering = SteeringConfig::default();
+    let wall = Aabb::from_center(Vec2::new(0.0, 0.0), Vec2::new(0.05, 0.2));
+    let radius = EnemyKind::Chaser.radius();
+
+    // Within avoidance distance of wall face, but not touching it
+    let near = [game::Enemy::new(EnemyKind::Chaser, Vec2::new(-0.05 - radius - steering.avoidance_distance * 0.5, 0.05))];
+    let correction = steering.steer(0, &near, &[0], &[wall]);
+    assert!(correction.x < 0.0);
+    assert!(correction.y.abs() < 1e-6);
+
+    // Near corner enemy is turned around it
+    let corner = [game::Enemy::new(EnemyKind::Chaser, Vec2::new(-0.05 - radius * 0.5, 0.2 + radius * 0.5))];
+    let correction = steering.steer(0, &corner, &[0], &[wall]);
+    assert!(correction.x < 0.0 && correction.y > 0.0);
+
+    let far = [game::Enemy::new(EnemyKind::Chaser, Vec2::new(-1.0, 0.05))];
+    assert_eq!(steering.steer(0, &far, &[0], &[wall]), Vec2::new(0.0, 0.0));
+    let without_avoidance = SteeringConfig { avoidance_weight: 0.0, ..steering };
+    assert_eq!(without_avoidance.steer(0, &near, &[0], &[wall]), Vec2::new(0.0, 0.0));
+}
+
+// Slowest speed towards wall enemy chasing player behind it had before reaching the wall
+fn approach_speed_before_contact(steering: SteeringConfig) -> f32 {
+    let mut engine = common::sturdy_engine();
+    engine.steering = steering;
+    engine.player.position = Vec2::new(1.0, 0.0);
+    // Wall between enemy and player, enemy starts slightly off its axis
+    let wall = Aabb::from_center(Vec2::new(0.0, 0.0), Vec2::new(0.05, 0.2));
+    engine.arena.obstacles.push(wall);
+    engine.enemies.push(game::Enemy::new(EnemyKind::Chaser, Vec2::new(-1.0, 0.05)));
+
+    let contact_distance = EnemyKind::Chaser.radius() * 1.01;
+    let mut slowest = f32::MAX;
+    for _ in 0..(3.0 / game::TICK_DURATION) as usize {
+        engine.update(&game::PlayerInput::new());
+        let enemy = &engine.enemies[0];
+        if (enemy.position - wall.closest_point(enemy.position)).length() < contact_distance {
+            break;
+        }
+        slowest = slowest.min(enemy.velocity.x);
+    }
+    slowest / EnemyKind::Chaser.stats().speed
+}
+
+#[test]
+fn enemies_slow_down_before_obstacles() {
+    // Velocity is steering output, arena resolution only moves enemies out of walls
+    assert!(approach_speed_before_contact(SteeringConfig::default()) < 0.5);
+    assert!(approach_speed_before_contact(SteeringConfig { avoidance_weight: 0.0, ..SteeringConfig::default() }) > 0.9);
+}
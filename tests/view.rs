@@ -0,0 +1,51 @@
+// Screen to world conversion shared by mouse aiming and rendering
+
+use pixel_mobs_gun_rs::game;
+use pixel_mobs_gun_rs::input::{InputMap, InputState};
+use pixel_mobs_gun_rs::view::{self, View};
+
+type Vec2 = game::Vec2;
+
+fn assert_near(lhs: Vec2, rhs: Vec2) {
+    assert!((lhs - rhs).length() < 1e-5, "{lhs:?} != {rhs:?}");
+}
+
+#[test]
+fn aspect_ratio_is_kept() {
+    // Shorter side always shows VIEW_EXTENT to both sides
+    assert_eq!(View::new(800, 600).half_size(), Vec2::new(4.0 / 3.0, 1.0) * view::VIEW_EXTENT);
+    assert_eq!(View::new(600, 800).half_size(), Vec2::new(1.0, 4.0 / 3.0) * view::VIEW_EXTENT);
+    assert_eq!(View::new(500, 500).half_size(), Vec2::new(1.0, 1.0) * view::VIEW_EXTENT);
+
+    // Wide window: right edge middle is further than top edge middle
+    let wide = View::new(1600, 900);
+    assert_near(wide.screen_to_world(Vec2::new(1600.0, 450.0)), Vec2::new(16.0 / 9.0, 0.0));
+    assert_near(wide.screen_to_world(Vec2::new(800.0, 0.0)), Vec2::new(0.0, 1.0));
+}
+
+#[test]
+fn conversion_round_trips_and_follows_center() {
+    let mut view = View::new(1280, 720);
+    view.center = Vec2::new(0.5, -0.25);
+
+    assert_near(view.screen_to_world(Vec2::new(640.0, 360.0)), view.center);
+    for world in [Vec2::new(0.0, 0.0), Vec2::new(1.2, 0.7), Vec2::new(-0.9, -0.4)] {
+        assert_near(view.screen_to_world(view.world_to_screen(world)), world);
+    }
+
+    view.set_screen_size(720, 1280);
+    let corner = view.screen_to_world(Vec2::new(0.0, 0.0));
+    assert_near(corner, view.center + Vec2::new(-1.0, 16.0 / 9.0) * view::VIEW_EXTENT);
+}
+
+#[test]
+fn mouse_aim_direction_is_correct_on_wide_window() {
+    let view = View::new(1600, 900);
+    let mut state = InputState::new(InputMap::default_map());
+    let player = Vec2::new(0.0, 0.0);
+
+    // Cursor 45 degrees up right of player on screen is aimed 45 degrees in world too
+    state.point("mouse", Vec2::new(800.0 + 200.0, 450.0 - 200.0));
+    let aim = state.player_input(player, &view).aim - player;
+    assert!((aim.x - aim.y).abs() < 1e-5 && aim.x > 0.0);
+}
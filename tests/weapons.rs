@@ -0,0 +1,143 @@
+// Weapon fire rate, magazines and bullet behaviours
+
+mod common;
+
+use pixel_mobs_gun_rs::game::{self, EnemyKind, WeaponKind};
+use pixel_mobs_gun_rs::weapon::{self, Weapon};
+
+type Vec2 = game::Vec2;
+
+fn trigger(fire: bool) -> game::PlayerInput {
+    game::PlayerInput { aim: Vec2::new(1.0, 0.0), fire, ..game::PlayerInput::new() }
+}
+
+fn count_shots(weapon: &mut Weapon, trigger: impl Fn(usize) -> bool, seconds: f32) -> usize {
+    (0..(seconds / game::TICK_DURATION) as usize)
+        .filter(|tick| weapon.update(trigger(*tick), game::TICK_DURATION))
+        .count()
+}
+
+#[test]
+fn every_weapon_has_name_and_sane_stats() {
+    for kind in weapon::ALL_WEAPON_KINDS {
+        let name = format!("{kind:?}").to_lowercase();
+        let stats = kind.stats();
+
+        assert_eq!(WeaponKind::from_name(&name), Some(kind));
+        assert!(stats.fire_interval > 0.0 && stats.reload_duration > 0.0);
+        assert!(stats.pellets > 0 && stats.magazine > 0);
+    }
+}
+
+#[test]
+fn automatic_weapon_fires_at_its_rate_while_held() {
+    let mut rifle = Weapon::new(WeaponKind::Rifle);
+    let stats = rifle.stats();
+    let seconds = stats.fire_interval * (stats.magazine - 1) as f32;
+
+    let shots = count_shots(&mut rifle, |_| true, seconds);
+    assert!(shots.abs_diff((seconds / stats.fire_interval) as usize) <= 1);
+}
+
+#[test]
+fn semi_automatic_weapon_needs_trigger_release() {
+    let mut pistol = Weapon::new(WeaponKind::Pistol);
+    assert_eq!(count_shots(&mut pistol, |_| true, 1.0), 1);
+
+    // Pressed every 0.25 seconds
+    let mut pistol = Weapon::new(WeaponKind::Pistol);
+    let press_period = (0.25 / game::TICK_DURATION).round() as usize;
+    assert_eq!(count_shots(&mut pistol, |tick| tick % press_period == 0, 1.0), 4);
+}
+
+#[test]
+fn empty_magazine_reloads() {
+    let mut rifle = Weapon::new(WeaponKind::Rifle);
+    let stats = rifle.stats();
+
+    // Magazine runs out, nothing is fired during reload
+    let shots = count_shots(&mut rifle, |_| true, stats.fire_interval * stats.magazine as f32 + stats.reload_duration * 0.5);
+    assert_eq!(shots, stats.magazine as usize);
+    assert!(rifle.is_reloading());
+
+    count_shots(&mut rifle, |_| false, stats.reload_duration);
+    assert_eq!(rifle.ammo, stats.magazine);
+
+    // Manual reload of full magazine does nothing
+    rifle.reload();
+    assert!(!rifle.is_reloading());
+}
+
+#[test]
+fn shotgun_fires_spread_of_pellets() {
+    let mut engine = common::armed_engine(WeaponKind::Shotgun);
+    let stats = WeaponKind::Shotgun.stats();
+    engine.update(&trigger(true));
+
+    assert_eq!(engine.bullets.len(), stats.pellets as usize);
+    for bullet in &engine.bullets {
+        let angle = bullet.velocity.y.atan2(bullet.velocity.x);
+        assert!(angle.abs() <= stats.spread * 0.5 + 1e-4);
+        assert!((bullet.velocity.length() - stats.bullet_speed).abs() < 1e-4);
+        assert_eq!(bullet.damage, stats.damage);
+    }
+
+    let first_angle = engine.bullets.as_slice()[0].velocity.y;
+    assert!(engine.bullets.iter().any(|bullet| bullet.velocity.y != first_angle));
+}
+
+#[test]
+fn railgun_bullet_pierces_enemies() {
+    let mut engine = common::armed_engine(WeaponKind::Railgun);
+    for x in [0.3, 0.5, 0.7] {
+        engine.enemies.push(game::Enemy::new(EnemyKind::Brute, Vec2::new(x, 0.0)));
+    }
+    engine.player.health = 1.0e9;
+    engine.update(&trigger(true));
+
+    let mut hits = 0;
+    for _ in 0..(0.3 / game::TICK_DURATION) as usize {
+        engine.update(&trigger(false));
+        hits += engine.events.iter().filter(|event| matches!(event, game::GameEvent::EnemyHit { .. })).count();
+    }
+
+    // Every enemy is hit exactly once, bullet flies on
+    assert_eq!(hits, 3);
+    assert_eq!(engine.bullets.len(), 1);
+}
+
+#[test]
+fn piercing_bullet_hits_enemy_it_starts_inside() {
+    let mut engine = game::Engine::new();
+    engine.enemies.push(game::Enemy::new(EnemyKind::Brute, Vec2::new(0.5, 0.0)));
+
+    // Bullet already pierced another enemy and is now inside this one
+    let mut bullet = game::Bullet::new(Vec2::new(0.5, 0.0), Vec2::new(1.0, 0.0));
+    bullet.pierce = 8;
+    bullet.hits = 1;
+    bullet.hit_enemies[0] = 1000;
+    engine.bullets.spawn(bullet);
+
+    let mut hits = 0;
+    for _ in 0..10 {
+        engine.update(&game::PlayerInput::new());
+        hits += engine.events.iter().filter(|event| matches!(event, game::GameEvent::EnemyHit { .. })).count();
+    }
+    assert_eq!(hits, 1);
+}
+
+#[test]
+fn switching_weapons_keeps_their_ammo() {
+    let mut engine = common::armed_engine(WeaponKind::Pistol);
+    engine.update(&trigger(true));
+    let ammo = engine.player.weapon().ammo;
+    assert_eq!(ammo, WeaponKind::Pistol.stats().magazine - 1);
+
+    engine.update(&game::PlayerInput { switch_weapon: Some(1), ..game::PlayerInput::new() });
+    engine.update(&game::PlayerInput { switch_weapon: Some(0), ..game::PlayerInput::new() });
+    assert_eq!(engine.player.weapon().ammo, ammo);
+
+    // Unknown slot is ignored
+    engine.update(&game::PlayerInput { switch_weapon: Some(99), ..game::PlayerInput::new() });
+    assert_eq!(engine.player.weapon().kind, WeaponKind::Pistol);
+}